@@ -0,0 +1,199 @@
+use common::{money::Money, Entry};
+use rust_decimal::Decimal;
+
+/// Combines the entries of every batch booking into one entry for the total
+/// of the batch, with the amount of each transaction in the memo, e.g.
+/// `Alice 100.00, Bob 200.00`. YNAB can't import split transactions from
+/// CSV. Entries that aren't part of a complete batch are kept as they are.
+pub fn combine_batches(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut combined = vec![];
+    let mut rest = entries.as_slice();
+
+    while let Some(first) = rest.first() {
+        let count = batch_length(rest);
+        combined.push(match count {
+            0 => first.to_owned(),
+            _ => combine(&rest[..count]),
+        });
+        rest = &rest[count.max(1)..];
+    }
+
+    combined
+}
+
+/// The number of entries of the batch at the start of the entries, or 0.
+fn batch_length(entries: &[Entry]) -> usize {
+    let Some(split) = entries.first().and_then(|e| e.split) else {
+        return 0;
+    };
+    let complete = split.index == 1
+        && entries.len() >= split.count
+        && entries[..split.count].iter().enumerate().all(|(i, e)| {
+            e.account == entries[0].account
+                && e.split
+                    .is_some_and(|s| s.index == i + 1 && s.count == split.count)
+        });
+
+    match complete {
+        true => split.count,
+        false => 0,
+    }
+}
+
+fn signed(entry: &Entry) -> Option<Money> {
+    match (entry.inflow, entry.outflow) {
+        (Some(inflow), _) => Some(inflow),
+        (None, Some(mut outflow)) => {
+            outflow.amount = -outflow.amount;
+            Some(outflow)
+        }
+        (None, None) => None,
+    }
+}
+
+/// The value of a field when every entry of the batch has the same value.
+fn shared<T: PartialEq + Clone>(
+    batch: &[Entry],
+    field: impl Fn(&Entry) -> &Option<T>,
+) -> Option<T> {
+    let value = field(&batch[0]);
+    match batch.iter().all(|e| field(e) == value) {
+        true => value.to_owned(),
+        false => None,
+    }
+}
+
+fn combine(batch: &[Entry]) -> Entry {
+    let amounts = batch.iter().map(signed).collect::<Vec<_>>();
+    let total = amounts.iter().flatten().map(|m| m.amount).sum::<Decimal>();
+    let outflow = total.is_sign_negative() && !total.is_zero();
+
+    let memo = batch
+        .iter()
+        .zip(amounts)
+        .enumerate()
+        .map(|(index, (entry, amount))| {
+            let label = entry
+                .payee
+                .to_owned()
+                .or(entry.memo.to_owned())
+                .unwrap_or_else(|| format!("Transaction {}", index + 1));
+            match amount {
+                Some(mut amount) => {
+                    if outflow {
+                        amount.amount = -amount.amount;
+                    }
+                    format!("{} {}", label, amount)
+                }
+                None => label,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut entry = batch[0].to_owned();
+    let money = entry.inflow.or(entry.outflow).map(|mut money| {
+        money.amount = total.abs();
+        money
+    });
+    (entry.inflow, entry.outflow) = match outflow {
+        true => (None, money),
+        false => (money, None),
+    };
+    entry.payee = shared(batch, |e| &e.payee);
+    entry.memo = Some(memo);
+    entry.counterparty_account = shared(batch, |e| &e.counterparty_account);
+    entry.counterparty_bic = shared(batch, |e| &e.counterparty_bic);
+    entry.ultimate_debtor = shared(batch, |e| &e.ultimate_debtor);
+    entry.ultimate_creditor = shared(batch, |e| &e.ultimate_creditor);
+    entry.exchange_rate = shared(batch, |e| &e.exchange_rate);
+    entry.original_amount = None;
+    // The parser numbers the reference of each transaction, e.g. `REF/1`.
+    entry.reference = entry
+        .reference
+        .map(|r| r.strip_suffix("/1").map(|r| r.to_owned()).unwrap_or(r));
+    entry.split = None;
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use common::Split;
+
+    use super::*;
+
+    fn entry(payee: &str, outflow: &str, split: Option<Split>) -> Entry {
+        let mut entry = Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 12, 22).expect("date to be valid"),
+            Some(payee.to_string()),
+            Some("Salary December".to_string()),
+            None,
+            Some(Money::parse(outflow, "EUR").expect("amount to be valid")),
+        );
+        entry.reference = split.map(|s| format!("REF/{}", s.index));
+        entry.split = split;
+        entry
+    }
+
+    fn split(index: usize, count: usize) -> Option<Split> {
+        Some(Split { index, count })
+    }
+
+    #[test]
+    fn combines_batch_into_one_entry() {
+        let entries = vec![
+            entry("Carol", "50", None),
+            entry("Alice", "100", split(1, 2)),
+            entry("Bob", "200", split(2, 2)),
+        ];
+
+        let entries = combine_batches(entries);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].payee, Some("Carol".to_string()));
+        assert_eq!(entries[1].payee, None);
+        assert_eq!(
+            entries[1].memo,
+            Some("Alice 100.00, Bob 200.00".to_string())
+        );
+        assert_eq!(
+            entries[1].outflow,
+            Some(Money::parse("300", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(entries[1].reference, Some("REF".to_string()));
+        assert_eq!(entries[1].split, None);
+    }
+
+    #[test]
+    fn nets_transactions_in_both_directions() {
+        let mut refund = entry("Bob", "20", split(2, 2));
+        (refund.inflow, refund.outflow) = (refund.outflow, None);
+        let entries = vec![entry("Alice", "100", split(1, 2)), refund];
+
+        let entries = combine_batches(entries);
+
+        assert_eq!(
+            entries[0].outflow,
+            Some(Money::parse("80", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(
+            entries[0].memo,
+            Some("Alice 100.00, Bob -20.00".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_incomplete_batch() {
+        let entries = vec![
+            entry("Alice", "100", split(1, 3)),
+            entry("Bob", "200", split(2, 3)),
+        ];
+
+        let combined = combine_batches(entries.to_owned());
+
+        assert_eq!(combined, entries);
+    }
+}
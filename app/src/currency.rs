@@ -0,0 +1,289 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use common::{
+    money::{Currency, ExchangeRate, Money},
+    Entry,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CurrencyError {
+    #[error("Error reading exchange rates: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("Invalid exchange rate on line {line}: {reason}")]
+    InvalidRate { line: u64, reason: String },
+    #[error("No exchange rate from {from} to {to} on or before {date}")]
+    MissingRate {
+        from: Currency,
+        to: Currency,
+        date: NaiveDate,
+    },
+}
+
+#[derive(Deserialize)]
+struct RateRecord {
+    date: String,
+    from: Currency,
+    to: Currency,
+    rate: String,
+}
+
+/// Exchange rates by date, read from a CSV file with a `date,from,to,rate`
+/// header, e.g. `2023-07-20,USD,EUR,0.9180` for one dollar in euros.
+#[derive(Debug, Default)]
+pub struct ExchangeRates {
+    rates: Vec<(NaiveDate, ExchangeRate)>,
+}
+
+impl ExchangeRates {
+    pub fn parse(contents: &str) -> Result<Self, CurrencyError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+        let headers = reader.headers()?.clone();
+
+        let mut rates = vec![];
+        for record in reader.records() {
+            let record = record?;
+            let invalid = |reason: String| CurrencyError::InvalidRate {
+                line: record.position().map_or(0, |p| p.line()),
+                reason,
+            };
+
+            let rate: RateRecord = record
+                .deserialize(Some(&headers))
+                .map_err(|e| invalid(e.to_string()))?;
+            let date = NaiveDate::parse_from_str(&rate.date, "%Y-%m-%d")
+                .map_err(|_| invalid(format!("invalid date '{}'", rate.date)))?;
+            let value = Decimal::from_str(&rate.rate)
+                .ok()
+                .filter(|r| *r > Decimal::ZERO)
+                .ok_or_else(|| invalid(format!("invalid rate '{}'", rate.rate)))?;
+
+            rates.push((
+                date,
+                ExchangeRate {
+                    source: rate.from,
+                    target: rate.to,
+                    rate: value,
+                },
+            ));
+        }
+        rates.sort_by_key(|(date, _)| *date);
+
+        Ok(ExchangeRates { rates })
+    }
+
+    /// The latest rate on or before the date that converts between the
+    /// currencies, in either direction.
+    pub fn find(&self, from: Currency, to: Currency, date: NaiveDate) -> Option<&ExchangeRate> {
+        self.rates
+            .iter()
+            .rev()
+            .filter(|(d, _)| *d <= date)
+            .map(|(_, rate)| rate)
+            .find(|r| [(from, to), (to, from)].contains(&(r.source, r.target)))
+    }
+}
+
+/// Converts entries into the currency of the budget, with the rate the bank
+/// applied or else the rate table.
+pub struct CurrencyConverter {
+    currency: Currency,
+    rates: ExchangeRates,
+}
+
+impl CurrencyConverter {
+    pub fn new(currency: Currency, rates: ExchangeRates) -> Self {
+        CurrencyConverter { currency, rates }
+    }
+
+    /// Converts the amounts of an entry in another currency and keeps the
+    /// amount before conversion, unless the bank already gave one.
+    pub fn convert(&self, entry: &mut Entry) -> Result<(), CurrencyError> {
+        let Some(money) = entry.inflow.or(entry.outflow) else {
+            return Ok(());
+        };
+        if money.currency == self.currency {
+            return Ok(());
+        }
+
+        let convert = |m: Money| {
+            entry
+                .exchange_rate
+                .and_then(|r| r.convert(m, self.currency))
+                .or_else(|| {
+                    self.rates
+                        .find(m.currency, self.currency, entry.date)
+                        .and_then(|r| r.convert(m, self.currency))
+                })
+                .ok_or(CurrencyError::MissingRate {
+                    from: m.currency,
+                    to: self.currency,
+                    date: entry.date,
+                })
+        };
+        let inflow = entry.inflow.map(convert).transpose()?;
+        let outflow = entry.outflow.map(convert).transpose()?;
+
+        entry.inflow = inflow;
+        entry.outflow = outflow;
+        entry.original_amount.get_or_insert(money);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: &str = "date,from,to,rate
+2023-07-01,USD,EUR,0.9100
+2023-07-15, USD, EUR, 0.9200
+2023-07-15,EUR,GBP,0.8600
+";
+
+    fn money(amount: &str, currency: &str) -> Money {
+        Money::parse(amount, currency).expect("amount to be valid")
+    }
+
+    fn currency(code: &str) -> Currency {
+        code.parse().expect("currency to be valid")
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 7, day).expect("date to be valid")
+    }
+
+    fn entry(day: u32, outflow: Money) -> Entry {
+        Entry::new(
+            "NL00BANK0000000000".to_string(),
+            date(day),
+            None,
+            None,
+            None,
+            Some(outflow),
+        )
+    }
+
+    #[test]
+    fn finds_latest_rate_in_either_direction() {
+        let rates = ExchangeRates::parse(RATES).expect("rates to be parsed");
+
+        let rate = |from: &str, to: &str, day: u32| {
+            rates
+                .find(currency(from), currency(to), date(day))
+                .map(|r| r.rate.to_string())
+        };
+        assert_eq!(rate("USD", "EUR", 10), Some("0.9100".to_string()));
+        assert_eq!(rate("USD", "EUR", 20), Some("0.9200".to_string()));
+        assert_eq!(rate("GBP", "EUR", 20), Some("0.8600".to_string()));
+        assert_eq!(rate("USD", "EUR", 1), Some("0.9100".to_string()));
+        assert_eq!(
+            rates.find(currency("USD"), currency("EUR"), NaiveDate::MIN),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rates() {
+        for (contents, line) in [
+            ("date,from,to,rate\n2023-07-01,USD,EUR,abc\n", 2),
+            (
+                "date,from,to,rate\n2023-07-01,USD,EUR,0.9\n2023-13-01,USD,EUR,0.9\n",
+                3,
+            ),
+            ("date,from,to,rate\n2023-07-01,usd,EUR,0.9\n", 2),
+            ("date,from,to,rate\n2023-07-01,USD,EUR,0\n", 2),
+        ] {
+            assert!(
+                matches!(
+                    ExchangeRates::parse(contents),
+                    Err(CurrencyError::InvalidRate { line: l, .. }) if l == line
+                ),
+                "{}",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_bank_rate_over_rate_table() {
+        let converter = CurrencyConverter::new(
+            Currency::EUR,
+            ExchangeRates::parse(RATES).expect("rates to be parsed"),
+        );
+        let mut entry = entry(20, money("10.00", "USD"));
+        entry.exchange_rate = Some(ExchangeRate {
+            source: currency("USD"),
+            target: Currency::EUR,
+            rate: Decimal::new(95, 2),
+        });
+
+        converter
+            .convert(&mut entry)
+            .expect("entry to be converted");
+
+        assert_eq!(entry.outflow, Some(money("9.50", "EUR")));
+        assert_eq!(entry.original_amount, Some(money("10.00", "USD")));
+    }
+
+    #[test]
+    fn falls_back_to_rate_table() {
+        let converter = CurrencyConverter::new(
+            Currency::EUR,
+            ExchangeRates::parse(RATES).expect("rates to be parsed"),
+        );
+        let mut entries = [
+            entry(20, money("10.00", "USD")),
+            entry(20, money("8.60", "GBP")),
+            entry(20, money("5.00", "EUR")),
+        ];
+
+        for entry in entries.iter_mut() {
+            converter.convert(entry).expect("entry to be converted");
+        }
+
+        assert_eq!(entries[0].outflow, Some(money("9.20", "EUR")));
+        assert_eq!(entries[1].outflow, Some(money("10.00", "EUR")));
+        assert_eq!(entries[2].outflow, Some(money("5.00", "EUR")));
+        assert_eq!(entries[2].original_amount, None);
+    }
+
+    #[test]
+    fn keeps_instructed_amount() {
+        let converter = CurrencyConverter::new(
+            Currency::EUR,
+            ExchangeRates::parse(RATES).expect("rates to be parsed"),
+        );
+        let mut entry = entry(20, money("8.60", "GBP"));
+        entry.original_amount = Some(money("11.00", "USD"));
+
+        converter
+            .convert(&mut entry)
+            .expect("entry to be converted");
+
+        assert_eq!(entry.outflow, Some(money("10.00", "EUR")));
+        assert_eq!(entry.original_amount, Some(money("11.00", "USD")));
+    }
+
+    #[test]
+    fn fails_without_rate() {
+        let converter = CurrencyConverter::new(Currency::EUR, ExchangeRates::default());
+        let mut entry = entry(20, money("10.00", "USD"));
+
+        let error = converter
+            .convert(&mut entry)
+            .expect_err("entry not to be converted");
+
+        assert_eq!(
+            error.to_string(),
+            "No exchange rate from USD to EUR on or before 2023-07-20"
+        );
+        assert_eq!(entry.outflow, Some(money("10.00", "USD")));
+    }
+}
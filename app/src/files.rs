@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use file_system::list_files::{ListFiles, ListFilesError};
+use thiserror::Error;
+
+const STATEMENT_EXTENSIONS: [&str; 7] = ["xml", "csv", "sta", "940", "mt940", "ofx", "qfx"];
+
+#[derive(Debug, Error)]
+pub enum FilesError {
+    #[error("Error listing files: {0}")]
+    ListFiles(#[from] ListFilesError),
+    #[error("No statement files found for '{0}'")]
+    NoFiles(String),
+    #[error("Accounts '{0}' and '{1}' would both be written to {2}, give them distinct aliases")]
+    DuplicateOutputFile(String, String, String),
+}
+
+/// Expands the inputs into a sorted list of statement files. A directory
+/// yields the statement files directly inside it, anything else is treated
+/// as a glob pattern, so plain file paths work too.
+pub fn resolve_files(
+    list_files: &ListFiles,
+    inputs: &[String],
+) -> Result<Vec<PathBuf>, FilesError> {
+    let mut files = vec![];
+
+    for input in inputs {
+        let found = match Path::new(input).is_dir() {
+            true => list_files
+                .list_files(input)?
+                .into_iter()
+                .filter(|path| is_statement_file(path))
+                .collect::<Vec<_>>(),
+            false => list_files.glob(input)?,
+        };
+
+        if found.is_empty() {
+            return Err(FilesError::NoFiles(input.to_owned()));
+        }
+        files.extend(found);
+    }
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+fn is_statement_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| STATEMENT_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// The name of the output file of an account, as a single path component.
+/// Characters other than letters, digits, `-` and `_` in the alias or
+/// account become `_`, so `../savings` can't escape the output directory.
+pub fn output_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+
+    format!("{}.csv", name)
+}
+
+/// The [`output_file_name`] of every name, in the same order. Fails when two
+/// names map to the same file, so no account overwrites another's output.
+pub fn output_file_names(names: &[&str]) -> Result<Vec<String>, FilesError> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+
+    names
+        .iter()
+        .map(|name| {
+            let file_name = output_file_name(name);
+            match seen.insert(file_name.to_owned(), name) {
+                Some(other) => Err(FilesError::DuplicateOutputFile(
+                    other.to_owned(),
+                    name.to_string(),
+                    file_name,
+                )),
+                None => Ok(file_name),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn sorts_and_dedups_matches() {
+        let list_files = ListFiles::nullable(RefCell::new(Ok(vec![
+            PathBuf::from("statements/2023-05.xml"),
+            PathBuf::from("statements/2023-04.xml"),
+        ])));
+
+        let result = resolve_files(
+            &list_files,
+            &[
+                "statements/*.xml".to_string(),
+                "statements/2023-0?.xml".to_string(),
+            ],
+        )
+        .expect("files to be resolved");
+
+        assert_eq!(
+            result,
+            vec![
+                PathBuf::from("statements/2023-04.xml"),
+                PathBuf::from("statements/2023-05.xml")
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_without_matches() {
+        let list_files = ListFiles::nullable(RefCell::new(Ok(vec![])));
+
+        let result = resolve_files(&list_files, &["statements/*.xml".to_string()]);
+
+        assert!(matches!(result, Err(FilesError::NoFiles(_))));
+    }
+
+    #[test]
+    fn output_file_name_is_one_path_component() {
+        for (name, file_name) in [
+            ("savings", "savings.csv"),
+            ("NL00BANK0000000000", "NL00BANK0000000000.csv"),
+            ("../savings", "___savings.csv"),
+            ("credit/card", "credit_card.csv"),
+            ("..", "__.csv"),
+        ] {
+            assert_eq!(output_file_name(name), file_name);
+        }
+    }
+
+    #[test]
+    fn fails_on_duplicate_output_file_name() {
+        let result = output_file_names(&["credit/card", "savings", "credit_card"]);
+
+        assert!(matches!(
+            result,
+            Err(FilesError::DuplicateOutputFile(first, second, file_name))
+                if first == "credit/card" && second == "credit_card" && file_name == "credit_card.csv"
+        ));
+    }
+
+    #[test]
+    fn output_file_names_keep_order() {
+        let result = output_file_names(&["savings", "main"]).expect("names to be distinct");
+
+        assert_eq!(result, vec!["savings.csv", "main.csv"]);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use asn_csv_parser::AsnCsvParser;
+use camt053_parser::Camt053Parser;
+use common::parser::StatementParser;
+use csv_profile_parser::{model::CsvProfile, CsvProfileParser};
+use mt940_parser::Mt940Parser;
+use ofx_parser::OfxParser;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("Unknown format '{0}', expected one of {1}")]
+    UnknownFormat(String, String),
+    #[error("Unrecognized statement format, use --format to select one of {0}")]
+    Undetected(String),
+    #[error("Ambiguous statement format, use --format to select one of {0}")]
+    Ambiguous(String),
+}
+
+/// Every statement format the app can import, followed by the CSV profiles
+/// from the config.
+pub fn parsers(csv_profiles: &BTreeMap<String, CsvProfile>) -> Vec<Box<dyn StatementParser>> {
+    let mut parsers: Vec<Box<dyn StatementParser>> = vec![
+        Box::new(Camt053Parser::create()),
+        Box::new(AsnCsvParser::create()),
+        Box::new(Mt940Parser::create()),
+        Box::new(OfxParser::create()),
+    ];
+    for (name, profile) in csv_profiles {
+        parsers.push(Box::new(CsvProfileParser::create(name, profile.clone())));
+    }
+
+    parsers
+}
+
+/// Selects the parser named by `format`, or the only parser that detects the
+/// contents when no format is given.
+pub fn select<'a>(
+    parsers: &'a [Box<dyn StatementParser>],
+    format: Option<&str>,
+    contents: &[u8],
+) -> Result<&'a dyn StatementParser, FormatError> {
+    let names = |parsers: &[&dyn StatementParser]| {
+        parsers
+            .iter()
+            .map(|p| p.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let parsers = parsers.iter().map(|p| p.as_ref()).collect::<Vec<_>>();
+
+    if let Some(format) = format {
+        return parsers
+            .iter()
+            .find(|p| p.name().eq(format))
+            .copied()
+            .ok_or_else(|| FormatError::UnknownFormat(format.to_owned(), names(&parsers)));
+    }
+
+    let detected = parsers
+        .iter()
+        .filter(|p| p.detect(contents))
+        .copied()
+        .collect::<Vec<_>>();
+
+    match detected[..] {
+        [parser] => Ok(parser),
+        [] => Err(FormatError::Undetected(names(&parsers))),
+        _ => Err(FormatError::Ambiguous(names(&detected))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{parser::ParseStatementError, Statement};
+    use csv_profile_parser::model::Column;
+
+    use super::*;
+
+    struct AnythingParser {}
+
+    impl StatementParser for AnythingParser {
+        fn name(&self) -> &str {
+            "anything"
+        }
+
+        fn detect(&self, _contents: &[u8]) -> bool {
+            true
+        }
+
+        fn parse_statements(
+            &self,
+            _contents: &[u8],
+        ) -> Result<Vec<Statement>, ParseStatementError> {
+            Ok(vec![])
+        }
+    }
+
+    const CAMT053: &[u8] = br#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02"><BkToCstmrStmt>"#;
+    const MT940: &[u8] =
+        b":20:0000000001\n:25:NL81ASNB9999999999\n:28C:00001/001\n:60F:C230401EUR1000,00\n";
+    const OFX: &[u8] = b"OFXHEADER:100\r\nDATA:OFXSGML\r\n\r\n<OFX>\r\n<SIGNONMSGSRSV1>\r\n";
+    const ASN_CSV: &[u8] = b"23-04-2023,NL64ASNB8830237094,,,,,,EUR,1206.36,EUR,-27.86,23-04-2023,23-04-2023,7913,BEA,40776623,'ref','description',0\n";
+
+    #[test]
+    fn detects_format() {
+        let parsers = parsers(&BTreeMap::new());
+
+        let camt053 = select(&parsers, None, CAMT053).expect("format to be detected");
+        let asn_csv = select(&parsers, None, ASN_CSV).expect("format to be detected");
+        let mt940 = select(&parsers, None, MT940).expect("format to be detected");
+        let ofx = select(&parsers, None, OFX).expect("format to be detected");
+
+        assert_eq!(camt053.name(), "camt053");
+        assert_eq!(asn_csv.name(), "asn-csv");
+        assert_eq!(mt940.name(), "mt940");
+        assert_eq!(ofx.name(), "ofx");
+    }
+
+    #[test]
+    fn fails_on_undetected_format() {
+        let parsers = parsers(&BTreeMap::new());
+
+        let result = select(&parsers, None, b"Date;Amount\n");
+
+        assert!(matches!(result, Err(FormatError::Undetected(_))));
+    }
+
+    #[test]
+    fn detects_csv_profile() {
+        let mut profile = CsvProfile::new(
+            "NL00BANK0000000000",
+            Column::Name("Date".to_string()),
+            "%Y-%m-%d",
+            Column::Name("Amount".to_string()),
+        );
+        profile.delimiter = ';';
+        let parsers = parsers(&BTreeMap::from([("bank".to_string(), profile)]));
+
+        let selected = select(&parsers, None, b"Date;Amount\n2023-04-23;-1.50\n")
+            .expect("format to be detected");
+
+        assert_eq!(selected.name(), "bank");
+    }
+
+    #[test]
+    fn format_overrides_ambiguous_detection() {
+        let mut parsers = parsers(&BTreeMap::new());
+        parsers.push(Box::new(AnythingParser {}));
+
+        let ambiguous = select(&parsers, None, CAMT053);
+        let selected = select(&parsers, Some("camt053"), CAMT053).expect("format to be selected");
+        let unknown = select(&parsers, Some("qif"), CAMT053);
+
+        assert!(matches!(
+            ambiguous,
+            Err(FormatError::Ambiguous(names)) if names == "camt053, anything"
+        ));
+        assert_eq!(selected.name(), "camt053");
+        assert!(matches!(unknown, Err(FormatError::UnknownFormat(..))));
+    }
+}
@@ -1,70 +1,357 @@
-use clap::Parser;
+mod batch;
+mod currency;
+mod files;
+mod formats;
+mod memo;
+mod merge;
+mod reversal;
+mod transfer;
+
+use clap::{Parser, Subcommand, ValueEnum};
 use home::home_dir;
-use regex::Regex;
 use serde::Deserialize;
-use std::{collections::HashMap, error::Error, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
 
-use camt053_parser::Camt053Parser;
-use ynab_csv::YnabCsvSerializer;
+use common::{money::Currency, normalize_account, ClearedState};
+use csv_profile_parser::model::CsvProfile;
+use currency::{CurrencyConverter, ExchangeRates};
+use file_system::{file_reader::FileReader, list_files::ListFiles};
+use import_ledger::ImportLedger;
+use rules::{model::RuleConfig, RulesEngine};
+use ynab_csv::{
+    date_format::DateFormat,
+    import_id::{ImportIdGenerator, ImportIdMode},
+    YnabCsvSerializer,
+};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, subcommand_negates_reqs = true)]
 struct Args {
-    file: String,
-    #[arg(short, long, default_value_t = String::from(".config/transaction-parser"))]
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Statement files, directories or glob patterns.
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Statement format, detected per file when omitted.
+    #[arg(short, long)]
+    format: Option<String>,
+    #[arg(short, long, global = true, default_value_t = String::from(".config/transaction-parser"))]
     config_dir: String,
+    /// Only output entries without payee, without recording them in the
+    /// import ledger.
     #[arg(short, long, default_value_t = false)]
     show_empty_payee: bool,
-    #[arg(short, long)]
-    account: String,
+    #[arg(short, long, required_unless_present = "all_accounts")]
+    account: Option<String>,
+    /// Write one CSV per account into the output directory.
+    #[arg(long, default_value_t = false, conflicts_with = "account")]
+    all_accounts: bool,
+    #[arg(short, long, default_value_t = String::from("."), requires = "all_accounts")]
+    output_dir: String,
+    #[arg(long, default_value_t = false)]
+    debug_rules: bool,
+    #[arg(long, value_enum, default_value_t = ImportIdMode::Ynab)]
+    import_id: ImportIdMode,
+    #[arg(long, value_enum)]
+    date_format: Option<DateFormat>,
+    #[arg(long, default_value_t = false)]
+    skip_reconcile: bool,
+    #[arg(long, default_value_t = false)]
+    drop_reversal_pairs: bool,
+    #[arg(long, default_value_t = false)]
+    include_pending: bool,
+    #[arg(long, value_enum, default_value_t = BatchMode::Individual)]
+    batch_mode: BatchMode,
+    #[arg(long, default_value_t = false)]
+    memo_counterparty_account: bool,
+    /// Output every transaction and don't record them in the import ledger.
+    #[arg(long, default_value_t = false)]
+    ignore_ledger: bool,
+    /// Skip entries that can't be parsed, with a warning, instead of failing
+    /// the whole file. Statements with skipped entries aren't reconciled,
+    /// the other statements in the file are.
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    /// Currency of the YNAB budget, EUR when neither this nor the config
+    /// sets it. Entries in other currencies are converted.
+    #[arg(long)]
+    budget_currency: Option<Currency>,
+    /// CSV file with `date,from,to,rate` exchange rates, used when the bank
+    /// doesn't supply the rate of an entry.
+    #[arg(long)]
+    exchange_rates: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage the ledger of imported transactions.
+    #[command(subcommand)]
+    Ledger(LedgerCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum LedgerCommand {
+    /// List the imported transactions.
+    List {
+        #[arg(short, long)]
+        account: Option<String>,
+    },
+    /// Forget an imported transaction so the next run outputs it again.
+    Forget {
+        import_id: String,
+        #[arg(short, long)]
+        account: Option<String>,
+    },
+    /// Forget all imported transactions.
+    Reset {
+        #[arg(short, long)]
+        account: Option<String>,
+    },
+}
+
+/// How entries expanded from a batch booking are written.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum BatchMode {
+    /// As unrelated transactions.
+    Individual,
+    /// As one transaction for the total of the batch, with the amount of
+    /// every transaction in the memo.
+    Split,
 }
 
 #[derive(Deserialize, Debug)]
 struct Config {
     account_alias: HashMap<String, String>,
+    #[serde(default)]
     payee_regex: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    rules: Vec<RuleConfig>,
+    date_format: Option<DateFormat>,
+    budget_currency: Option<Currency>,
+    /// Exchange rates CSV, relative to the config directory.
+    exchange_rates: Option<String>,
+    /// CSV layouts by format name, selectable like the built-in formats.
+    #[serde(default)]
+    csv_profiles: BTreeMap<String, CsvProfile>,
 }
 
-struct PayeeRegex {
-    payee: String,
-    regex: Regex,
+/// Maps every aliased account, normalized with [`normalize_account`], to its
+/// alias. When an account has several aliases the alphabetically first one
+/// is used.
+fn account_names(account_alias: &HashMap<String, String>) -> HashMap<String, &str> {
+    let mut aliases = account_alias.iter().collect::<Vec<_>>();
+    aliases.sort();
+
+    let mut names = HashMap::new();
+    for (alias, account) in aliases {
+        names
+            .entry(normalize_account(account))
+            .or_insert(alias.as_str());
+    }
+    names
+}
+
+fn run_ledger_command(
+    command: LedgerCommand,
+    ledger: &ImportLedger,
+    account_alias: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    let resolve =
+        |account: Option<String>| account.map(|a| account_alias.get(&a).cloned().unwrap_or(a));
+
+    match command {
+        LedgerCommand::List { account } => {
+            let account = resolve(account);
+            for record in ledger.records()? {
+                if account
+                    .as_ref()
+                    .is_none_or(|a| normalize_account(&record.account) == normalize_account(a))
+                {
+                    println!("{} {}", record.account, record.import_id);
+                }
+            }
+        }
+        LedgerCommand::Forget { import_id, account } => {
+            let removed = ledger.forget(&import_id, resolve(account).as_deref())?;
+            eprintln!("Forgot {} imported transactions", removed);
+        }
+        LedgerCommand::Reset { account } => {
+            let removed = ledger.reset(resolve(account).as_deref())?;
+            eprintln!("Forgot {} imported transactions", removed);
+        }
+    }
+
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
     let home_dir = home_dir().expect("Home dir to be found.");
-    let config_path = Path::new(&home_dir)
-        .join(&args.config_dir)
-        .join("config.yaml");
-    let config_yaml = fs::read_to_string(config_path)?;
+    let config_dir = Path::new(&home_dir).join(&args.config_dir);
+    let config_yaml = fs::read_to_string(config_dir.join("config.yaml"))?;
     let config: Config = serde_yaml::from_str(&config_yaml)?;
+    let ledger = ImportLedger::create(config_dir.join("ledger.jsonl"));
+
+    if let Some(Command::Ledger(command)) = args.command {
+        return run_ledger_command(command, &ledger, &config.account_alias);
+    }
+
+    // Accounts are IBANs or other identifiers such as card numbers, which
+    // match however they're spaced or cased.
+    let account = args
+        .account
+        .as_ref()
+        .map(|a| normalize_account(config.account_alias.get(a).unwrap_or(a)));
+    let selected = |a: &String| {
+        account
+            .as_ref()
+            .is_none_or(|account| normalize_account(a).eq(account))
+    };
+
+    let mut rule_configs = config.rules;
+    for rule in rule_configs.iter_mut() {
+        if let Some(alias) = rule.when.account.as_ref() {
+            rule.when.account = config
+                .account_alias
+                .get(alias)
+                .cloned()
+                .or(rule.when.account.take());
+        }
+    }
+    let mut payees = config.payee_regex.keys().collect::<Vec<_>>();
+    payees.sort();
+    for payee in payees {
+        for pattern in config.payee_regex[payee].iter() {
+            rule_configs.push(RuleConfig::payee_regex(payee, pattern));
+        }
+    }
+    let rules_engine = RulesEngine::new(rule_configs)?;
+
+    let parsers = formats::parsers(&config.csv_profiles);
+    let ynab_csv_serializer =
+        YnabCsvSerializer::create(args.date_format.or(config.date_format).unwrap_or_default());
+    let file_reader = FileReader::new();
+    let exchange_rates = match args
+        .exchange_rates
+        .as_ref()
+        .map(PathBuf::from)
+        .or(config.exchange_rates.as_ref().map(|p| config_dir.join(p)))
+    {
+        Some(path) => ExchangeRates::parse(&file_reader.read_file(&path.to_string_lossy())?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?,
+        None => ExchangeRates::default(),
+    };
+    let currency_converter = CurrencyConverter::new(
+        args.budget_currency
+            .or(config.budget_currency)
+            .unwrap_or(Currency::EUR),
+        exchange_rates,
+    );
+
+    let mut files = vec![];
+    for path in files::resolve_files(&ListFiles::new(), &args.files)? {
+        // Formats are detected from the start of the file, so formats that
+        // read in parts never hold the whole file in memory.
+        let mut reader = file_reader.open_buffered(&path.to_string_lossy())?;
+        let parser = formats::select(&parsers, args.format.as_deref(), reader.fill_buf()?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let statements = parser
+            .read_statements(&mut reader, args.lenient)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        for (statement, warnings) in statements.iter() {
+            for warning in warnings.iter() {
+                eprintln!("{}: skipped {}", path.display(), warning);
+            }
 
-    let account = config
-        .account_alias
-        .get(&args.account)
-        .unwrap_or(&args.account);
-
-    let mut payee_regex_list: Vec<PayeeRegex> = vec![];
-    for (key, list) in config.payee_regex.into_iter() {
-        for r in list.iter() {
-            payee_regex_list.push(PayeeRegex {
-                payee: key.to_owned(),
-                regex: Regex::new(r)?,
-            });
+            if !args.skip_reconcile && warnings.is_empty() && selected(&statement.account) {
+                statement
+                    .reconcile()
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                if let (None, Some(closing)) =
+                    (statement.opening_balance, statement.closing_balance)
+                {
+                    eprintln!(
+                        "{}: {} has no opening balance to reconcile, compare its closing balance of {} {}",
+                        path.display(),
+                        statement.account,
+                        closing,
+                        closing.currency
+                    );
+                }
+            }
         }
+
+        files.push(
+            statements
+                .into_iter()
+                .flat_map(|(s, _)| s.entries)
+                .collect(),
+        );
     }
 
-    let camt053_parser = Camt053Parser::create();
-    let ynab_csv_serializer = YnabCsvSerializer::create();
-    let xml = fs::read_to_string(&args.file)?;
-    let entries = camt053_parser.parse_file(&xml)?;
+    let mut entries = merge::merge_entries(files);
+    if args.batch_mode == BatchMode::Split {
+        entries = batch::combine_batches(entries);
+    }
+    // Import IDs and transfers use the booked amount, so they don't change
+    // with the rates.
+    ImportIdGenerator::new(args.import_id).generate(&mut entries);
+    transfer::detect_transfers(&mut entries, &config.account_alias);
+    for entry in entries.iter_mut().filter(|e| selected(&e.account)) {
+        currency_converter
+            .convert(entry)
+            .map_err(|e| format!("{}: {}", entry.account, e))?;
+    }
 
     let mut entries = entries
         .into_iter()
-        .filter(|e| e.account.eq(account))
+        .filter(|e| selected(&e.account))
+        .filter(|e| args.include_pending || e.cleared == ClearedState::Cleared)
         .collect::<Vec<_>>();
 
+    if !args.ignore_ledger {
+        entries = ledger.filter_new(entries)?;
+    }
+
+    if args.drop_reversal_pairs {
+        entries = reversal::drop_reversal_pairs(entries);
+    }
+
+    entries.retain_mut(|entry| {
+        let outcome = rules_engine.apply(entry);
+
+        if args.debug_rules {
+            eprintln!(
+                "{} {} {:?}: {}",
+                entry.date,
+                entry.account,
+                entry.payee,
+                match outcome.fired.is_empty() {
+                    true => "unmatched".to_string(),
+                    false => outcome.fired.join(", "),
+                }
+            );
+        }
+
+        !outcome.skip
+    });
+    transfer::set_transfer_payees(&mut entries);
+    reversal::annotate_reversals(&mut entries);
+    memo::append_original_amount(&mut entries);
+    if args.memo_counterparty_account {
+        memo::append_counterparty_account(&mut entries);
+    }
+
+    // Listing entries without payee is a diagnostic, not an import.
+    let record = !args.ignore_ledger && !args.show_empty_payee;
     if args.show_empty_payee {
         entries = entries
             .into_iter()
@@ -72,9 +359,51 @@ fn main() -> Result<(), Box<dyn Error>> {
             .collect::<Vec<_>>();
     }
 
-    let ynab_csv = ynab_csv_serializer.serialize(entries)?;
+    if !args.all_accounts {
+        let ynab_csv = ynab_csv_serializer.serialize(entries.to_owned())?;
+
+        println!("{}", ynab_csv);
+
+        if record {
+            ledger.record(&entries)?;
+        }
+
+        return Ok(());
+    }
 
-    println!("{}", ynab_csv);
+    let mut accounts: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for entry in entries {
+        accounts
+            .entry(entry.account.to_owned())
+            .or_default()
+            .push(entry);
+    }
+
+    let names = account_names(&config.account_alias);
+    let output_dir = Path::new(&args.output_dir);
+    let file_names = files::output_file_names(
+        &accounts
+            .keys()
+            .map(|account| {
+                names
+                    .get(&normalize_account(account))
+                    .copied()
+                    .unwrap_or(account)
+            })
+            .collect::<Vec<_>>(),
+    )?;
+    fs::create_dir_all(output_dir)?;
+    for ((_, entries), file_name) in accounts.into_iter().zip(file_names) {
+        let path = output_dir.join(file_name);
+        let count = entries.len();
+
+        fs::write(&path, ynab_csv_serializer.serialize(entries.to_owned())?)?;
+        if record {
+            ledger.record(&entries)?;
+        }
+
+        eprintln!("{}: {} entries", path.display(), count);
+    }
 
     Ok(())
 }
@@ -0,0 +1,101 @@
+use common::Entry;
+
+/// Appends the counterparty account to the memo of every entry that has one.
+pub fn append_counterparty_account(entries: &mut [Entry]) {
+    for entry in entries.iter_mut() {
+        if let Some(account) = entry.counterparty_account.as_ref() {
+            entry.memo = Some(match entry.memo.as_ref() {
+                Some(memo) => format!("{} ({})", memo, account),
+                None => account.to_owned(),
+            });
+        }
+    }
+}
+
+/// Appends the original amount to the memo of every entry that was paid in
+/// another currency, e.g. `Coffee (12.00 USD)`.
+pub fn append_original_amount(entries: &mut [Entry]) {
+    for entry in entries.iter_mut() {
+        let currency = entry.inflow.or(entry.outflow).map(|m| m.currency);
+        let Some(original) = entry
+            .original_amount
+            .filter(|o| Some(o.currency) != currency)
+        else {
+            continue;
+        };
+
+        let original = format!("{} {}", original, original.currency);
+        entry.memo = Some(match entry.memo.as_ref() {
+            Some(memo) => format!("{} ({})", memo, original),
+            None => original,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use common::money::Money;
+
+    use super::*;
+
+    #[test]
+    fn appends_counterparty_account_to_memo() {
+        let entry = |memo: Option<&str>, account: Option<&str>| {
+            let mut entry = Entry::new(
+                "NL00BANK0000000000".to_string(),
+                NaiveDate::from_ymd_opt(2023, 4, 1).expect("date to be valid"),
+                None,
+                memo.map(|m| m.to_string()),
+                None,
+                None,
+            );
+            entry.counterparty_account = account.map(|a| a.to_string());
+            entry
+        };
+        let mut entries = vec![
+            entry(Some("Invoice"), Some("NL61COBA0733974775")),
+            entry(None, Some("NL61COBA0733974775")),
+            entry(Some("Invoice"), None),
+        ];
+
+        append_counterparty_account(&mut entries);
+
+        assert_eq!(
+            entries[0].memo,
+            Some("Invoice (NL61COBA0733974775)".to_string())
+        );
+        assert_eq!(entries[1].memo, Some("NL61COBA0733974775".to_string()));
+        assert_eq!(entries[2].memo, Some("Invoice".to_string()));
+    }
+
+    #[test]
+    fn appends_original_amount_to_memo() {
+        let money = |amount: &str, currency: &str| {
+            Some(Money::parse(amount, currency).expect("amount to be valid"))
+        };
+        let entry = |memo: Option<&str>, original: Option<Money>| {
+            let mut entry = Entry::new(
+                "NL00BANK0000000000".to_string(),
+                NaiveDate::from_ymd_opt(2023, 7, 12).expect("date to be valid"),
+                None,
+                memo.map(|m| m.to_string()),
+                None,
+                money("11.05", "EUR"),
+            );
+            entry.original_amount = original;
+            entry
+        };
+        let mut entries = vec![
+            entry(Some("AMAZON.COM"), money("12", "USD")),
+            entry(None, money("12.00", "USD")),
+            entry(Some("Coffee"), money("11.05", "EUR")),
+        ];
+
+        append_original_amount(&mut entries);
+
+        assert_eq!(entries[0].memo, Some("AMAZON.COM (12.00 USD)".to_string()));
+        assert_eq!(entries[1].memo, Some("12.00 USD".to_string()));
+        assert_eq!(entries[2].memo, Some("Coffee".to_string()));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use common::Entry;
+
+/// Merges the entries of several statement files into one list sorted by
+/// date. Entries that an earlier file already contained are dropped, so
+/// overlapping statements don't import a transaction twice. Identical
+/// entries within one file are all kept.
+pub fn merge_entries(files: Vec<Vec<Entry>>) -> Vec<Entry> {
+    let mut merged: Vec<Entry> = vec![];
+    // Entries of earlier files by account and date, so each entry is only
+    // compared with the entries it could be equal to.
+    let mut earlier: HashMap<(String, NaiveDate), Vec<usize>> = HashMap::new();
+
+    for entries in files {
+        let existing = merged.len();
+        let mut seen = vec![false; existing];
+
+        for entry in entries {
+            let duplicate = earlier
+                .get(&(entry.account.to_owned(), entry.date))
+                .and_then(|indices| {
+                    indices
+                        .iter()
+                        .copied()
+                        .find(|i| !seen[*i] && merged[*i].eq(&entry))
+                });
+
+            match duplicate {
+                Some(index) => seen[index] = true,
+                None => merged.push(entry),
+            }
+        }
+
+        for (index, entry) in merged.iter().enumerate().skip(existing) {
+            earlier
+                .entry((entry.account.to_owned(), entry.date))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    merged.sort_by_key(|e| e.date);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use common::money::Money;
+
+    use super::*;
+
+    fn entry(day: u32, payee: &str) -> Entry {
+        Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, day).expect("date to be valid"),
+            Some(payee.to_string()),
+            None,
+            None,
+            Some(Money::parse("10", "EUR").expect("amount to be valid")),
+        )
+    }
+
+    #[test]
+    fn merges_overlapping_files() {
+        let result = merge_entries(vec![
+            vec![entry(3, "Shop"), entry(2, "Cafe"), entry(2, "Cafe")],
+            vec![entry(2, "Cafe"), entry(2, "Cafe"), entry(1, "Bakery")],
+        ]);
+
+        assert_eq!(
+            result,
+            vec![
+                entry(1, "Bakery"),
+                entry(2, "Cafe"),
+                entry(2, "Cafe"),
+                entry(3, "Shop")
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_repeated_entries_missing_from_earlier_file() {
+        let result = merge_entries(vec![
+            vec![entry(2, "Cafe")],
+            vec![entry(2, "Cafe"), entry(2, "Cafe")],
+        ]);
+
+        assert_eq!(result, vec![entry(2, "Cafe"), entry(2, "Cafe")]);
+    }
+
+    #[test]
+    fn keeps_equal_entries_of_other_accounts() {
+        let mut other_account = entry(2, "Cafe");
+        other_account.account = "NL86ASNB8830237086".to_string();
+
+        let result = merge_entries(vec![vec![entry(2, "Cafe")], vec![other_account.clone()]]);
+
+        assert_eq!(result, vec![entry(2, "Cafe"), other_account]);
+    }
+}
@@ -0,0 +1,142 @@
+use common::Entry;
+
+/// Prefixes the memo of every reversal entry so it stands out in YNAB.
+pub fn annotate_reversals(entries: &mut [Entry]) {
+    for entry in entries.iter_mut().filter(|e| e.reversal) {
+        entry.memo = Some(match entry.memo.as_ref() {
+            Some(memo) => format!("Reversal: {}", memo),
+            None => "Reversal".to_string(),
+        });
+    }
+}
+
+/// Drops every reversal together with the original entry it cancels: the
+/// most recent entry on or before the reversal date on the same account,
+/// with the same amount in the opposite direction and the same counterparty.
+/// Reversals without a matching original are kept.
+pub fn drop_reversal_pairs(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut dropped = vec![false; entries.len()];
+
+    for (index, reversal) in entries.iter().enumerate().filter(|(_, e)| e.reversal) {
+        let original = entries
+            .iter()
+            .enumerate()
+            .filter(|(i, e)| !dropped[*i] && !e.reversal && cancels(reversal, e))
+            .max_by_key(|(_, e)| e.date)
+            .map(|(i, _)| i);
+
+        if let Some(original) = original {
+            dropped[index] = true;
+            dropped[original] = true;
+        }
+    }
+
+    entries
+        .into_iter()
+        .zip(dropped)
+        .filter(|(_, dropped)| !dropped)
+        .map(|(entry, _)| entry)
+        .collect()
+}
+
+fn cancels(reversal: &Entry, original: &Entry) -> bool {
+    let same_counterparty = match (
+        reversal.counterparty_account.as_ref(),
+        original.counterparty_account.as_ref(),
+    ) {
+        (Some(reversal), Some(original)) => reversal.eq(original),
+        _ => reversal.payee.eq(&original.payee),
+    };
+
+    reversal.account.eq(&original.account)
+        && original.date <= reversal.date
+        && reversal.inflow.eq(&original.outflow)
+        && reversal.outflow.eq(&original.inflow)
+        && same_counterparty
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use common::money::Money;
+
+    use super::*;
+
+    fn entry(day: u32, payee: &str, inflow: Option<&str>, outflow: Option<&str>) -> Entry {
+        let money = |a: &str| Money::parse(a, "EUR").expect("amount to be valid");
+
+        Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, day).expect("date to be valid"),
+            Some(payee.to_string()),
+            Some("Memo".to_string()),
+            inflow.map(money),
+            outflow.map(money),
+        )
+    }
+
+    fn reversal(day: u32, payee: &str, inflow: Option<&str>, outflow: Option<&str>) -> Entry {
+        let mut entry = entry(day, payee, inflow, outflow);
+        entry.reversal = true;
+        entry
+    }
+
+    #[test]
+    fn annotates_reversal_memo() {
+        let mut entries = vec![
+            entry(1, "Shop", None, Some("10")),
+            reversal(2, "Shop", Some("10"), None),
+        ];
+
+        annotate_reversals(&mut entries);
+
+        assert_eq!(entries[0].memo, Some("Memo".to_string()));
+        assert_eq!(entries[1].memo, Some("Reversal: Memo".to_string()));
+    }
+
+    #[test]
+    fn drops_reversal_with_its_original() {
+        let entries = vec![
+            reversal(3, "Shop", Some("10"), None),
+            entry(2, "Shop", None, Some("10")),
+            entry(1, "Shop", None, Some("10")),
+            entry(1, "Other", None, Some("5")),
+        ];
+
+        let result = drop_reversal_pairs(entries);
+
+        assert_eq!(
+            result,
+            vec![
+                entry(1, "Shop", None, Some("10")),
+                entry(1, "Other", None, Some("5"))
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_reversal_without_original() {
+        let entries = vec![
+            entry(4, "Shop", None, Some("10")),
+            reversal(3, "Shop", Some("10"), None),
+            entry(2, "Other", None, Some("10")),
+            entry(1, "Shop", None, Some("12")),
+        ];
+
+        let result = drop_reversal_pairs(entries.to_owned());
+
+        assert_eq!(result, entries);
+    }
+
+    #[test]
+    fn matches_counterparty_account_over_payee() {
+        let mut original = entry(1, "BEN NEDERLAND", None, Some("10"));
+        original.counterparty_account = Some("NL61COBA0733974775".to_string());
+        let mut reversed = reversal(2, "BEN", Some("10"), None);
+        reversed.counterparty_account = Some("NL61COBA0733974775".to_string());
+
+        let result = drop_reversal_pairs(vec![original, reversed]);
+
+        assert_eq!(result, vec![]);
+    }
+}
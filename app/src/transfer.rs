@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use common::{normalize_account, Entry};
+
+use crate::account_names;
+
+/// Days the two sides of a transfer may be booked apart.
+const MAX_DAYS_APART: i64 = 3;
+
+/// Marks entries between our own accounts as transfers, with the alias of
+/// the account on the other side. [`set_transfer_payees`] turns them into
+/// YNAB transfers.
+///
+/// Entries are matched on their counterparty account. The other side of a
+/// matched transfer is marked too when its own statement lacks the
+/// counterparty account, so this should run on the entries of all accounts
+/// that are imported together, with the amounts as booked.
+pub fn detect_transfers(entries: &mut [Entry], account_alias: &HashMap<String, String>) {
+    let names = account_names(account_alias);
+
+    let mut matched = vec![false; entries.len()];
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let name = entry
+            .counterparty_account
+            .as_ref()
+            .map(|account| normalize_account(account))
+            .filter(|account| account.ne(&normalize_account(&entry.account)))
+            .and_then(|account| names.get(&account));
+
+        if let Some(name) = name {
+            entry.transfer = Some(name.to_string());
+            matched[index] = true;
+        }
+    }
+
+    for index in 0..entries.len() {
+        if !matched[index] {
+            continue;
+        }
+        let Some(name) = names.get(&normalize_account(&entries[index].account)) else {
+            continue;
+        };
+
+        let other_side = (0..entries.len())
+            .filter(|i| !matched[*i] && is_other_side(&entries[index], &entries[*i]))
+            .min_by_key(|i| (entries[index].date - entries[*i].date).num_days().abs());
+
+        if let Some(other_side) = other_side {
+            entries[other_side].transfer = Some(name.to_string());
+            matched[other_side] = true;
+        }
+    }
+}
+
+/// Sets the payee of transfers to `Transfer : <alias>`, so YNAB links both
+/// sides. This runs after the rules, so a payee rule can't undo it.
+pub fn set_transfer_payees(entries: &mut [Entry]) {
+    for entry in entries.iter_mut() {
+        if let Some(name) = entry.transfer.as_ref() {
+            entry.payee = Some(format!("Transfer : {}", name));
+        }
+    }
+}
+
+fn is_other_side(transfer: &Entry, other: &Entry) -> bool {
+    transfer
+        .counterparty_account
+        .as_ref()
+        .is_some_and(|account| normalize_account(account) == normalize_account(&other.account))
+        && other.counterparty_account.as_ref().is_none_or(|account| {
+            normalize_account(account) == normalize_account(&transfer.account)
+        })
+        && transfer.inflow.eq(&other.outflow)
+        && transfer.outflow.eq(&other.inflow)
+        && (transfer.date - other.date).num_days().abs() <= MAX_DAYS_APART
+}
+
+#[cfg(test)]
+mod tests {
+    use camt053_parser::{model::*, Camt053Parser};
+
+    use super::*;
+
+    fn aliases() -> HashMap<String, String> {
+        HashMap::from([
+            ("main".to_string(), "NL00BANK0000000001".to_string()),
+            ("savings".to_string(), "NL00BANK0000000002".to_string()),
+        ])
+    }
+
+    fn stmt(iban: &str, ntry: Vec<Ntry>) -> BkToCstmrStmtItem {
+        BkToCstmrStmtItem::Stmt(Stmt {
+            acct: Acct {
+                id: Id::Iban(iban.to_string()),
+            },
+            bal: vec![],
+            ntry,
+        })
+    }
+
+    fn ntry(
+        amount: &str,
+        credit_debit_indicator: CdtDbtIndValue,
+        date: &str,
+        counterparty: Option<&str>,
+    ) -> Ntry {
+        let mut ntry = Ntry::new(
+            amount,
+            credit_debit_indicator,
+            date,
+            None,
+            Some("J Doe".to_string()),
+        );
+        if let Some(rltd_pties) = ntry.ntry_dtls.tx_dtls[0].rltd_pties.as_mut() {
+            rltd_pties.cdtr_acct = counterparty.map(|iban| Acct {
+                id: Id::Iban(iban.to_string()),
+            });
+        }
+        ntry
+    }
+
+    fn parse(items: Vec<BkToCstmrStmtItem>) -> Vec<Entry> {
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt { items },
+        };
+
+        Camt053Parser::create_nullable(xml_document)
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed")
+    }
+
+    #[test]
+    fn marks_both_sides_by_counterparty_account() {
+        let mut entries = parse(vec![
+            stmt(
+                "NL00BANK0000000001",
+                vec![ntry(
+                    "50",
+                    CdtDbtIndValue::Dbit,
+                    "2023-04-01",
+                    Some("NL00BANK0000000002"),
+                )],
+            ),
+            stmt(
+                "NL00BANK0000000002",
+                vec![ntry(
+                    "50",
+                    CdtDbtIndValue::Crdt,
+                    "2023-04-01",
+                    Some("NL00BANK0000000001"),
+                )],
+            ),
+        ]);
+
+        detect_transfers(&mut entries, &aliases());
+        set_transfer_payees(&mut entries);
+
+        assert_eq!(entries[0].payee, Some("Transfer : savings".to_string()));
+        assert_eq!(entries[1].payee, Some("Transfer : main".to_string()));
+    }
+
+    #[test]
+    fn marks_other_side_without_counterparty_account() {
+        let mut entries = parse(vec![
+            stmt(
+                "NL00BANK0000000001",
+                vec![ntry(
+                    "50",
+                    CdtDbtIndValue::Dbit,
+                    "2023-04-01",
+                    Some("NL00BANK0000000002"),
+                )],
+            ),
+            stmt(
+                "NL00BANK0000000002",
+                vec![
+                    ntry("50", CdtDbtIndValue::Crdt, "2023-04-10", None),
+                    ntry("50", CdtDbtIndValue::Crdt, "2023-04-02", None),
+                    ntry("40", CdtDbtIndValue::Crdt, "2023-04-01", None),
+                ],
+            ),
+        ]);
+
+        detect_transfers(&mut entries, &aliases());
+        set_transfer_payees(&mut entries);
+
+        assert_eq!(entries[0].payee, Some("Transfer : savings".to_string()));
+        assert_eq!(entries[1].payee, Some("J Doe".to_string()));
+        assert_eq!(entries[2].payee, Some("Transfer : main".to_string()));
+        assert_eq!(entries[3].payee, Some("J Doe".to_string()));
+    }
+
+    #[test]
+    fn ignores_unknown_counterparty_accounts() {
+        let mut entries = parse(vec![stmt(
+            "NL00BANK0000000001",
+            vec![ntry(
+                "50",
+                CdtDbtIndValue::Dbit,
+                "2023-04-01",
+                Some("NL00BANK0000000003"),
+            )],
+        )]);
+
+        detect_transfers(&mut entries, &aliases());
+        set_transfer_payees(&mut entries);
+
+        assert_eq!(entries[0].payee, Some("J Doe".to_string()));
+    }
+
+    #[test]
+    fn matches_aliased_accounts_of_either_kind() {
+        let aliases = HashMap::from([
+            ("main".to_string(), "nl00 bank 0000 0000 01".to_string()),
+            ("card".to_string(), "5412 7500 0000 1234".to_string()),
+        ]);
+        let mut entries = parse(vec![
+            stmt(
+                "NL00BANK0000000001",
+                vec![ntry(
+                    "50",
+                    CdtDbtIndValue::Dbit,
+                    "2023-04-01",
+                    Some("5412750000001234"),
+                )],
+            ),
+            BkToCstmrStmtItem::Stmt(Stmt {
+                acct: Acct {
+                    id: Id::Othr(Othr {
+                        id: "5412750000001234".to_string(),
+                        schme_nm: None,
+                    }),
+                },
+                bal: vec![],
+                ntry: vec![ntry("50", CdtDbtIndValue::Crdt, "2023-04-01", None)],
+            }),
+        ]);
+
+        detect_transfers(&mut entries, &aliases);
+        set_transfer_payees(&mut entries);
+
+        assert_eq!(entries[0].payee, Some("Transfer : card".to_string()));
+        assert_eq!(entries[1].payee, Some("Transfer : main".to_string()));
+    }
+
+    #[test]
+    fn marks_transfers_without_changing_payee() {
+        let mut entries = parse(vec![stmt(
+            "NL00BANK0000000001",
+            vec![ntry(
+                "50",
+                CdtDbtIndValue::Dbit,
+                "2023-04-01",
+                Some("NL00BANK0000000002"),
+            )],
+        )]);
+
+        detect_transfers(&mut entries, &aliases());
+        entries[0].payee = Some("Rent".to_string());
+        set_transfer_payees(&mut entries);
+
+        assert_eq!(entries[0].transfer, Some("savings".to_string()));
+        assert_eq!(entries[0].payee, Some("Transfer : savings".to_string()));
+    }
+}
@@ -0,0 +1,331 @@
+pub mod model;
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::model::AsnCsvRecord;
+use common::{
+    money::{Money, MoneyError},
+    parser::{ParseStatementError, StatementParser},
+    split_payee_memo, Entry, Statement,
+};
+
+const COLUMN_COUNT: usize = 19;
+
+#[derive(Error, Debug)]
+pub enum ParseAsnCsvError {
+    #[error("Error parsing csv: {0}")]
+    ParseError(#[from] csv::Error),
+    #[error("Invalid amount '{value}' in column {column}: {source}")]
+    AmountError {
+        column: String,
+        value: String,
+        source: MoneyError,
+    },
+    #[error("Invalid date '{0}'")]
+    DateError(String),
+    #[error("Row {row}: {source}")]
+    InvalidRow {
+        row: usize,
+        source: Box<ParseAsnCsvError>,
+    },
+}
+
+fn invalid_row(row: usize) -> impl FnOnce(ParseAsnCsvError) -> ParseAsnCsvError {
+    move |source| ParseAsnCsvError::InvalidRow {
+        row,
+        source: Box::new(source),
+    }
+}
+
+fn parse_money(column: &str, value: &str, currency: &str) -> Result<Money, ParseAsnCsvError> {
+    Money::parse(value, currency).map_err(|source| ParseAsnCsvError::AmountError {
+        column: column.to_owned(),
+        value: value.to_owned(),
+        source,
+    })
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, ParseAsnCsvError> {
+    NaiveDate::parse_from_str(date, "%d-%m-%Y")
+        .map_err(|_| ParseAsnCsvError::DateError(date.to_owned()))
+}
+
+fn convert_record(record: AsnCsvRecord) -> Result<Entry, ParseAsnCsvError> {
+    let mut amount = parse_money("amount", &record.amount, &record.currency)?;
+
+    let (payee, memo) = split_payee_memo(record.counterparty_name, record.description);
+
+    let mut entry = Entry::new(
+        record.account,
+        parse_date(&record.booking_date)?,
+        payee,
+        memo.map(|s| {
+            s.split(' ')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+        None,
+        None,
+    );
+    entry.counterparty_account = record.counterparty_account;
+    entry.bank_transaction_code = record.internal_transaction_code;
+    entry.reference = record.transaction_id;
+
+    if amount.amount.is_sign_negative() {
+        amount.amount = -amount.amount;
+        entry.outflow = Some(amount);
+    } else {
+        entry.inflow = Some(amount);
+    }
+
+    Ok(entry)
+}
+
+/// Groups the records into one statement per account. The balances are
+/// derived from the balance before the first and last transaction.
+fn convert_records(records: Vec<AsnCsvRecord>) -> Result<Vec<Statement>, ParseAsnCsvError> {
+    let mut statements: Vec<Statement> = vec![];
+
+    for (index, record) in records.into_iter().enumerate() {
+        add_record(&mut statements, record).map_err(invalid_row(index + 1))?;
+    }
+
+    Ok(statements)
+}
+
+fn add_record(
+    statements: &mut Vec<Statement>,
+    record: AsnCsvRecord,
+) -> Result<(), ParseAsnCsvError> {
+    let balance_before = parse_money(
+        "balance_before",
+        &record.balance_before,
+        &record.account_currency,
+    )?;
+    let mut balance_after = balance_before;
+    balance_after.amount += parse_money("amount", &record.amount, &record.currency)?.amount;
+
+    let index = match statements
+        .iter()
+        .position(|s| s.account.eq(&record.account))
+    {
+        Some(index) => index,
+        None => {
+            statements.push(Statement {
+                account: record.account.to_owned(),
+                opening_balance: Some(balance_before),
+                closing_balance: None,
+                entries: vec![],
+            });
+            statements.len() - 1
+        }
+    };
+
+    let statement = &mut statements[index];
+    statement.closing_balance = Some(balance_after);
+    statement.entries.push(convert_record(record)?);
+
+    Ok(())
+}
+
+pub struct AsnCsvParser {
+    csv_reader: Box<dyn CsvReader>,
+}
+
+impl AsnCsvParser {
+    pub fn create_nullable(records: Vec<AsnCsvRecord>) -> Self {
+        AsnCsvParser {
+            csv_reader: Box::new(StubbedCsvReader { records }),
+        }
+    }
+
+    pub fn create() -> Self {
+        AsnCsvParser {
+            csv_reader: Box::new(RealCsvReader {}),
+        }
+    }
+
+    pub fn parse_file(&self, csv_contents: &str) -> Result<Vec<Entry>, ParseAsnCsvError> {
+        Ok(self
+            .parse_statements(csv_contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+
+    pub fn parse_statements(&self, csv_contents: &str) -> Result<Vec<Statement>, ParseAsnCsvError> {
+        let records = self.csv_reader.read_from_str(csv_contents)?;
+
+        convert_records(records)
+    }
+}
+
+impl StatementParser for AsnCsvParser {
+    fn name(&self) -> &str {
+        "asn-csv"
+    }
+
+    /// ASN exports have no header row, so the first row is checked for the
+    /// column count, a booking date and the account currency.
+    fn detect(&self, contents: &[u8]) -> bool {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .quote(b'\'')
+            .from_reader(contents);
+
+        let Some(Ok(record)) = reader.records().next() else {
+            return false;
+        };
+
+        record.len() == COLUMN_COUNT
+            && parse_date(&record[0]).is_ok()
+            && record[7].len() == 3
+            && record[7].chars().all(|c| c.is_ascii_uppercase())
+    }
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError> {
+        AsnCsvParser::parse_statements(self, std::str::from_utf8(contents)?)
+            .map_err(ParseStatementError::parser)
+    }
+}
+
+trait CsvReader {
+    fn read_from_str(&self, csv_contents: &str) -> Result<Vec<AsnCsvRecord>, ParseAsnCsvError>;
+}
+
+struct RealCsvReader {}
+
+impl CsvReader for RealCsvReader {
+    fn read_from_str(&self, csv_contents: &str) -> Result<Vec<AsnCsvRecord>, ParseAsnCsvError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .quote(b'\'')
+            .from_reader(csv_contents.as_bytes());
+
+        reader
+            .deserialize()
+            .enumerate()
+            .map(|(index, record)| {
+                record
+                    .map_err(ParseAsnCsvError::from)
+                    .map_err(invalid_row(index + 1))
+            })
+            .collect()
+    }
+}
+
+struct StubbedCsvReader {
+    records: Vec<AsnCsvRecord>,
+}
+
+impl CsvReader for StubbedCsvReader {
+    fn read_from_str(&self, _csv_contents: &str) -> Result<Vec<AsnCsvRecord>, ParseAsnCsvError> {
+        Ok(self.records.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_csv_parser() {
+        let asn_csv_parser = AsnCsvParser::create_nullable(vec![]);
+
+        assert_eq!(
+            asn_csv_parser
+                .parse_file("mocked,csv")
+                .expect("File to be parsed"),
+            vec![]
+        )
+    }
+
+    #[test]
+    fn test_csv_records_have_entries() {
+        let asn_csv_parser = AsnCsvParser::create_nullable(vec![
+            AsnCsvRecord::new(
+                "23-04-2023",
+                "-27.86",
+                "1206.36",
+                None,
+                Some("ALBERT HEIJN 1631     >GRONINGEN".to_string()),
+            ),
+            AsnCsvRecord::new(
+                "24-04-2023",
+                "100.00",
+                "1178.50",
+                Some("J Doe".to_string()),
+                Some("Rent  April".to_string()),
+            ),
+        ]);
+
+        let entries = asn_csv_parser
+            .parse_file("mocked,csv")
+            .expect("File to be parsed");
+
+        assert_eq!(
+            entries,
+            vec![
+                Entry::new(
+                    "NL00ASNB0000000000".to_string(),
+                    NaiveDate::from_ymd_opt(2023, 4, 23).expect("date to be valid"),
+                    Some("ALBERT HEIJN 1631".to_string()),
+                    Some("GRONINGEN".to_string()),
+                    None,
+                    Some(Money::parse("27.86", "EUR").expect("amount to be valid")),
+                ),
+                Entry::new(
+                    "NL00ASNB0000000000".to_string(),
+                    NaiveDate::from_ymd_opt(2023, 4, 24).expect("date to be valid"),
+                    Some("J Doe".to_string()),
+                    Some("Rent April".to_string()),
+                    Some(Money::parse("100.00", "EUR").expect("amount to be valid")),
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_records_have_statement_balances() {
+        let asn_csv_parser = AsnCsvParser::create_nullable(vec![
+            AsnCsvRecord::new("23-04-2023", "-27.86", "1206.36", None, None),
+            AsnCsvRecord::new("24-04-2023", "100.00", "1178.50", None, None),
+        ]);
+
+        let statements = asn_csv_parser
+            .parse_statements("mocked,csv")
+            .expect("File to be parsed");
+
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0].opening_balance,
+            Some(Money::parse("1206.36", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(
+            statements[0].closing_balance,
+            Some(Money::parse("1278.50", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(statements[0].reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn test_csv_record_with_invalid_date() {
+        let asn_csv_parser = AsnCsvParser::create_nullable(vec![AsnCsvRecord::new(
+            "2023-04-23",
+            "-27.86",
+            "1206.36",
+            None,
+            None,
+        )]);
+
+        let result = asn_csv_parser.parse_file("mocked,csv");
+
+        assert!(matches!(
+            result,
+            Err(ParseAsnCsvError::InvalidRow { row: 1, source })
+                if matches!(*source, ParseAsnCsvError::DateError(_))
+        ));
+    }
+}
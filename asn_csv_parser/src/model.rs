@@ -0,0 +1,58 @@
+use serde::Deserialize;
+
+/// A row of an ASN Bank CSV export. The export has no header row, so the
+/// fields are read in column order.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct AsnCsvRecord {
+    pub booking_date: String,
+    pub account: String,
+    pub counterparty_account: Option<String>,
+    pub counterparty_name: Option<String>,
+    pub address: Option<String>,
+    pub postal_code: Option<String>,
+    pub city: Option<String>,
+    pub account_currency: String,
+    pub balance_before: String,
+    pub currency: String,
+    pub amount: String,
+    pub journal_date: String,
+    pub value_date: String,
+    pub internal_transaction_code: Option<String>,
+    pub global_transaction_code: Option<String>,
+    pub transaction_id: Option<String>,
+    pub payment_reference: Option<String>,
+    pub description: Option<String>,
+    pub statement_number: Option<String>,
+}
+
+impl AsnCsvRecord {
+    pub fn new(
+        booking_date: &str,
+        amount: &str,
+        balance_before: &str,
+        counterparty_name: Option<String>,
+        description: Option<String>,
+    ) -> Self {
+        AsnCsvRecord {
+            booking_date: booking_date.to_string(),
+            account: "NL00ASNB0000000000".to_string(),
+            counterparty_account: None,
+            counterparty_name,
+            address: None,
+            postal_code: None,
+            city: None,
+            account_currency: "EUR".to_string(),
+            balance_before: balance_before.to_string(),
+            currency: "EUR".to_string(),
+            amount: amount.to_string(),
+            journal_date: booking_date.to_string(),
+            value_date: booking_date.to_string(),
+            internal_transaction_code: None,
+            global_transaction_code: None,
+            transaction_id: None,
+            payment_reference: None,
+            description,
+            statement_number: None,
+        }
+    }
+}
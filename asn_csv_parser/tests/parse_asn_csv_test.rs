@@ -0,0 +1,98 @@
+use std::fs;
+
+use asn_csv_parser::{AsnCsvParser, ParseAsnCsvError};
+use chrono::NaiveDate;
+use common::{money::Money, parser::StatementParser, Entry};
+
+#[test]
+fn name() {
+    let asn_csv_parser = AsnCsvParser::create();
+    let file_contents = fs::read_to_string("../resources/example.csv").expect("File to be read");
+
+    let mut result = asn_csv_parser
+        .parse_file(&file_contents)
+        .expect("csv file to be parsed");
+
+    let mut expected = Entry::new(
+        "NL64ASNB8830237094".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 23).expect("date to be valid"),
+        Some("ALBERT HEIJN 1631".to_string()),
+        Some(
+            "GRONINGEN23.04.2023 13U30 KV005 WW34CG MCC:5411 Contactloze betaling NLNEDERLAND"
+                .to_string(),
+        ),
+        None,
+        Some(Money::parse("27.86", "EUR").expect("amount to be valid")),
+    );
+    expected.bank_transaction_code = Some("7913".to_string());
+    expected.reference = Some("40776623".to_string());
+    assert_eq!(result.remove(0), expected);
+    assert_eq!(result, vec![]);
+}
+
+#[test]
+fn statements_reconcile() {
+    let asn_csv_parser = AsnCsvParser::create();
+    let file_contents = fs::read_to_string("../resources/example.csv").expect("File to be read");
+
+    let statements = asn_csv_parser
+        .parse_statements(&file_contents)
+        .expect("csv file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(
+        statements[0].opening_balance,
+        Some(Money::parse("1206.36", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(
+        statements[0].closing_balance,
+        Some(Money::parse("1178.50", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(statements[0].reconcile(), Ok(()));
+}
+
+#[test]
+fn detects_format() {
+    let asn_csv_parser = AsnCsvParser::create();
+    let csv = fs::read("../resources/example.csv").expect("File to be read");
+    let xml = fs::read("../camt053_parser/resources/example.xml").expect("File to be read");
+
+    assert!(asn_csv_parser.detect(&csv));
+    assert!(!asn_csv_parser.detect(&xml));
+}
+
+#[test]
+fn reports_row_and_column_of_invalid_amount() {
+    let asn_csv_parser = AsnCsvParser::create();
+    let file_contents = fs::read_to_string("../resources/example.csv").expect("File to be read");
+    let corrupted = file_contents.replace(",-27.86,", ",-27.8x,");
+
+    let result = asn_csv_parser.parse_statements(&corrupted);
+
+    let error = result.expect_err("amount to be invalid");
+    assert!(matches!(
+        &error,
+        ParseAsnCsvError::InvalidRow { row: 1, source }
+            if matches!(&**source, ParseAsnCsvError::AmountError { column, value, .. }
+                if column == "amount" && value == "-27.8x")
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Row 1: Invalid amount '-27.8x' in column amount: Invalid amount '-27.8x'"
+    );
+}
+
+#[test]
+fn reports_row_of_invalid_line() {
+    let asn_csv_parser = AsnCsvParser::create();
+    let file_contents = fs::read_to_string("../resources/example.csv").expect("File to be read");
+    let corrupted = format!("{}23-04-2023,NL64ASNB8830237094\n", file_contents);
+
+    let result = asn_csv_parser.parse_statements(&corrupted);
+
+    assert!(matches!(
+        result,
+        Err(ParseAsnCsvError::InvalidRow { row: 2, source })
+            if matches!(*source, ParseAsnCsvError::ParseError(_))
+    ));
+}
@@ -0,0 +1,138 @@
+//! Compares parsing a large statement into a document with streaming it, by
+//! time and by peak heap use. Run with `cargo bench -p camt053_parser`.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use camt053_parser::{Camt053Parser, EntryStream};
+
+const ENTRY_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+const RUNS: u32 = 5;
+
+/// Counts the bytes allocated on the heap and the peak since the last reset.
+struct CountingAllocator {
+    allocated: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = self.allocated.fetch_add(layout.size(), Ordering::Relaxed);
+            self.peak
+                .fetch_max(allocated + layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.allocated.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    allocated: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+/// The most heap memory in use while running `f`, on top of what was in use
+/// before.
+fn peak_allocation(f: impl FnOnce() -> usize) -> usize {
+    let before = ALLOCATOR.allocated.load(Ordering::Relaxed);
+    ALLOCATOR.peak.store(before, Ordering::Relaxed);
+    black_box(f());
+    ALLOCATOR.peak.load(Ordering::Relaxed) - before
+}
+
+fn statement(entry_count: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+<BkToCstmrStmt>
+<Stmt>
+<Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+"#,
+    );
+
+    for index in 0..entry_count {
+        xml.push_str(&format!(
+            r#"<Ntry>
+<NtryRef>{index}</NtryRef>
+<Amt Ccy="EUR">{}.{:02}</Amt>
+<CdtDbtInd>{}</CdtDbtInd>
+<Sts>BOOK</Sts>
+<BookgDt><Dt>2023-04-{:02}</Dt></BookgDt>
+<NtryDtls><TxDtls>
+<RltdPties><Cdtr><Nm>Payee {index}</Nm></Cdtr><CdtrAcct><Id><IBAN>NL12RABO0123456789</IBAN></Id></CdtrAcct></RltdPties>
+<RmtInf><Ustrd>Invoice {index}</Ustrd></RmtInf>
+</TxDtls></NtryDtls>
+</Ntry>
+"#,
+            index % 1000,
+            index % 100,
+            if index % 2 == 0 { "DBIT" } else { "CRDT" },
+            index % 28 + 1,
+        ));
+    }
+
+    xml.push_str("</Stmt>\n</BkToCstmrStmt>\n</Document>\n");
+    xml
+}
+
+fn time(runs: u32, mut f: impl FnMut() -> usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(f());
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let camt053_parser = Camt053Parser::create();
+    let mut stream_peaks = vec![];
+
+    for entry_count in ENTRY_COUNTS {
+        let xml = statement(entry_count);
+        let parse_file = || {
+            camt053_parser
+                .parse_file(&xml)
+                .expect("statement to be parsed")
+                .len()
+        };
+        let stream = || {
+            EntryStream::new(xml.as_bytes())
+                .try_fold(0, |count, entry| entry.map(|_| count + 1))
+                .expect("statement to be streamed")
+        };
+
+        let document_peak = peak_allocation(parse_file);
+        let stream_peak = peak_allocation(stream);
+        stream_peaks.push(stream_peak);
+
+        println!(
+            "{:>7} entries, {:>6} KiB: parse_file {:>10.2?} {:>8} KiB peak, EntryStream {:>10.2?} {:>8} KiB peak",
+            entry_count,
+            xml.len() / 1024,
+            time(RUNS, parse_file),
+            document_peak / 1024,
+            time(RUNS, stream),
+            stream_peak / 1024,
+        );
+    }
+
+    // Streaming a hundred times as many entries must not need much more
+    // memory than streaming the smallest statement.
+    let (first, last) = (stream_peaks[0], stream_peaks[stream_peaks.len() - 1]);
+    assert!(
+        last <= first * 2,
+        "EntryStream peak grew from {} to {} bytes",
+        first,
+        last
+    );
+}
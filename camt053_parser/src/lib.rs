@@ -1,81 +1,449 @@
 pub mod model;
-use model::CdtDbtIndValue;
-use quick_xml::de::from_str;
+mod reader;
+mod stream;
+mod version;
+use chrono::NaiveDate;
+use model::{CdtDbtIndValue, StsValue};
+use rust_decimal::Decimal;
+use std::{io::BufRead, str::FromStr};
 use thiserror::Error;
 
-use crate::model::{BkToCstmrStmtItem, XmlDocument};
-use common::Entry;
+use crate::{
+    model::{Bal, CcyXchg, Ntry, TxDtls, XmlDocument},
+    reader::{document_parts, DocumentReader, Part},
+};
+pub use crate::{
+    reader::{Location, Position},
+    stream::EntryStream,
+    version::Version,
+};
+use common::{
+    money::{Currency, ExchangeRate, Money, MoneyError},
+    parser::{ParseStatementError, StatementParser, SNIFF_LENGTH},
+    split_payee_memo, ClearedState, Entry, Split, Statement,
+};
+
+/// Namespaces of camt.053 statements, camt.052 reports and camt.054
+/// notifications.
+const NAMESPACES: [&str; 3] = [
+    "urn:iso:std:iso:20022:tech:xsd:camt.053",
+    "urn:iso:std:iso:20022:tech:xsd:camt.052",
+    "urn:iso:std:iso:20022:tech:xsd:camt.054",
+];
+const MESSAGES: [&str; 3] = [
+    "<BkToCstmrStmt",
+    "<BkToCstmrAcctRpt",
+    "<BkToCstmrDbtCdtNtfctn",
+];
 
 #[derive(Error, Debug)]
 pub enum ParseCamt053Error {
     #[error("Error opening file")]
     FileError(#[from] std::io::Error),
-    #[error("Error parsing xml")]
+    #[error("Error parsing xml: {0}")]
     ParseError(#[from] quick_xml::de::DeError),
+    #[error(transparent)]
+    AmountError(#[from] MoneyError),
+    #[error("Invalid date '{0}'")]
+    DateError(String),
+    #[error("Entry without booking or value date")]
+    MissingDate,
+    #[error("Invalid exchange rate '{0}'")]
+    ExchangeRateError(String),
+    #[error("Unsupported namespace '{0}', expected camt.052, camt.053 or camt.054 version 001.02, 001.04 or 001.08")]
+    UnsupportedNamespace(String),
+    #[error("Error reading xml: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    #[error("{source} in {element}")]
+    InvalidElement {
+        element: String,
+        source: Box<ParseCamt053Error>,
+    },
+    #[error("{source} at {location}{}", ntry_ref.as_ref().map(|r| format!(", NtryRef {}", r)).unwrap_or_default())]
+    Located {
+        location: Location,
+        ntry_ref: Option<String>,
+        source: Box<ParseCamt053Error>,
+    },
 }
 
-#[derive(Debug)]
-pub struct EntriesContainer {
-    pub entries: Vec<Entry>,
+impl ParseCamt053Error {
+    fn in_element(element: impl Into<String>) -> impl FnOnce(Self) -> Self {
+        move |source| ParseCamt053Error::InvalidElement {
+            element: element.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Adds the location of the failing element, unless the error already
+    /// has one.
+    fn located(self, mut location: Location, ntry_ref: Option<String>) -> Self {
+        let source = match self {
+            ParseCamt053Error::Located { .. } => return self,
+            ParseCamt053Error::InvalidElement { element, source } => {
+                location.path = format!("{}/{}", location.path, element);
+                *source
+            }
+            source => source,
+        };
+
+        ParseCamt053Error::Located {
+            location,
+            ntry_ref,
+            source: Box::new(source),
+        }
+    }
 }
 
-impl From<XmlDocument> for EntriesContainer {
-    fn from(value: XmlDocument) -> Self {
-        let mut container = EntriesContainer { entries: vec![] };
-
-        let items = value.bk_to_cstmr_stmt.items;
-
-        for item in items {
-            if let BkToCstmrStmtItem::Stmt(stmt) = item {
-                let account = stmt.acct.id.iban;
-                for item in stmt.ntry {
-                    let mut payee = item
-                        .ntry_dtls
-                        .tx_dtls
-                        .rltd_pties
-                        .and_then(|r| r.cdtr.or(r.dbtr).map(|c| c.nm));
-                    let mut memo = item.addtl_ntry_inf.or(item
-                        .ntry_dtls
-                        .tx_dtls
-                        .rmt_inf
-                        .and_then(|r| r.ustrd.first().cloned())
-                        .map(|s| s.to_owned()));
-
-                    if let Some(txt) = memo.as_ref() {
-                        let memo_split = txt.split('>').collect::<Vec<_>>();
-                        if memo_split.len() > 1 {
-                            payee = Some(memo_split[0].trim().to_owned());
-                            memo = Some(memo_split[1].trim().to_owned());
-                        }
-                    }
+fn parse_date(date: &str) -> Result<NaiveDate, ParseCamt053Error> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| ParseCamt053Error::DateError(date.to_owned()))
+}
 
-                    let mut inflow: Option<String> = None;
-                    let mut outflow: Option<String> = None;
+/// The signed amount of the first balance with one of the given type codes.
+fn find_balance(balances: &[Bal], codes: &[&str]) -> Result<Option<Money>, ParseCamt053Error> {
+    let Some(balance) = codes
+        .iter()
+        .find_map(|code| balances.iter().find(|b| b.code() == Some(code)))
+    else {
+        return Ok(None);
+    };
 
-                    match item.cdt_dbt_ind.content {
-                        CdtDbtIndValue::Dbit => outflow = Some(item.amt),
-                        CdtDbtIndValue::Crdt => inflow = Some(item.amt),
-                    }
+    let mut money = Money::parse(&balance.amt.value, &balance.amt.ccy)?;
+    if balance.cdt_dbt_ind.content == CdtDbtIndValue::Dbit {
+        money.amount = -money.amount;
+    }
+
+    Ok(Some(money))
+}
+
+/// Converts an entry into one `Entry` per transaction. Batch entries whose
+/// transaction amounts are missing or don't add up to the entry amount are
+/// kept as a single entry. Entries of intraday reports are never cleared.
+fn convert_ntry(
+    account: &str,
+    ntry: Ntry,
+    report: bool,
+    version: Version,
+) -> Result<Vec<Entry>, ParseCamt053Error> {
+    let amount = Money::parse(&ntry.amt.value, &ntry.amt.ccy)
+        .map_err(|e| ParseCamt053Error::in_element("Amt")(e.into()))?;
+
+    // Pending entries of intraday reports may only have a value date.
+    let (element, date) = [("BookgDt", &ntry.bookg_dt), ("ValDt", &ntry.val_dt)]
+        .into_iter()
+        .find_map(|(element, d)| d.as_ref().and_then(|d| d.date()).map(|d| (element, d)))
+        .ok_or(ParseCamt053Error::MissingDate)?;
+
+    let mut entry = Entry::new(
+        account.to_owned(),
+        parse_date(date).map_err(ParseCamt053Error::in_element(element))?,
+        None,
+        None,
+        None,
+        None,
+    );
+    entry.bank_transaction_code = ntry.bk_tx_cd.as_ref().and_then(|b| b.code());
+    entry.reference = ntry.ntry_ref.or(ntry.acct_svcr_ref);
+    entry.reversal = ntry.rvsl_ind.unwrap_or(false);
+    entry.cleared = match ntry.sts.as_ref().and_then(|s| s.value(version)) {
+        Some(StsValue::Book) | None => ClearedState::Cleared,
+        Some(StsValue::Pdng | StsValue::Info | StsValue::Futr | StsValue::Other) => {
+            ClearedState::Uncleared
+        }
+    };
+    if report {
+        entry.cleared = ClearedState::Uncleared;
+    }
+
+    let credit_debit_indicator = ntry.cdt_dbt_ind.content;
+    let mut tx_dtls = ntry.ntry_dtls.tx_dtls;
+
+    let tx_amounts = tx_dtls
+        .iter()
+        .enumerate()
+        .map(|(index, t)| {
+            t.tx_amt(version)
+                .map(|(path, a)| {
+                    Money::parse(&a.value, &a.ccy).map_err(|e| {
+                        ParseCamt053Error::in_element(format!(
+                            "NtryDtls/TxDtls[{}]/{}",
+                            index, path
+                        ))(e.into())
+                    })
+                })
+                .transpose()
+        })
+        .collect::<Result<Option<Vec<_>>, _>>()?
+        .filter(|amounts| {
+            amounts.len() > 1
+                && amounts.iter().all(|a| a.currency == amount.currency)
+                && amounts.iter().map(|a| a.amount).sum::<Decimal>() == amount.amount
+        });
+
+    let Some(tx_amounts) = tx_amounts else {
+        let tx = match tx_dtls.len() {
+            1 => tx_dtls.pop(),
+            _ => None,
+        };
+        let memo = ntry.addtl_ntry_inf.or(tx.as_ref().and_then(ustrd));
+
+        let mut entry = transaction_entry(
+            entry,
+            tx.as_ref(),
+            memo,
+            amount,
+            &credit_debit_indicator,
+            version,
+        );
+        if let Some(tx) = tx.as_ref() {
+            add_foreign_amount(&mut entry, tx, 0)?;
+        }
+
+        return Ok(vec![entry]);
+    };
+
+    let count = tx_amounts.len();
+    let mut entries = vec![];
+    for (index, (tx, amount)) in tx_dtls.iter().zip(tx_amounts).enumerate() {
+        let memo = ustrd(tx).or(ntry.addtl_ntry_inf.to_owned());
+
+        let mut entry = transaction_entry(
+            entry.to_owned(),
+            Some(tx),
+            memo,
+            amount,
+            &credit_debit_indicator,
+            version,
+        );
+        entry.reference = entry.reference.map(|r| format!("{}/{}", r, index + 1));
+        entry.split = Some(Split {
+            index: index + 1,
+            count,
+        });
+        add_foreign_amount(&mut entry, tx, index)?;
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
 
-                    container.entries.push(Entry::new(
-                        account.to_owned(),
-                        item.bookg_dt.dt,
-                        payee,
-                        memo.map(|s| {
-                            s.replace('\n', "")
-                                .split(' ')
-                                .filter(|s| !s.is_empty())
-                                .collect::<Vec<_>>()
-                                .join(" ")
-                        }),
-                        inflow,
-                        outflow,
-                    ));
+fn ustrd(tx: &TxDtls) -> Option<String> {
+    tx.rmt_inf.as_ref().and_then(|r| r.ustrd.first().cloned())
+}
+
+/// The rate of a currency exchange per unit of the rate's source currency.
+fn exchange_rate(ccy_xchg: &CcyXchg) -> Result<ExchangeRate, ParseCamt053Error> {
+    let rate = Decimal::from_str(&ccy_xchg.xchg_rate)
+        .ok()
+        .filter(|r| r.is_sign_positive() && !r.is_zero())
+        .ok_or_else(|| ParseCamt053Error::ExchangeRateError(ccy_xchg.xchg_rate.to_owned()))?;
+    let source: Currency = ccy_xchg.src_ccy.parse()?;
+    let target: Currency = ccy_xchg.trgt_ccy.parse()?;
+    let unit = ccy_xchg
+        .unit_ccy
+        .as_deref()
+        .map(str::parse::<Currency>)
+        .transpose()?;
+
+    Ok(match unit {
+        Some(unit) if unit == target => ExchangeRate {
+            source: target,
+            target: source,
+            rate,
+        },
+        _ => ExchangeRate {
+            source,
+            target,
+            rate,
+        },
+    })
+}
+
+/// Keeps the instructed amount of a payment in another currency and the
+/// rate the bank applied.
+fn add_foreign_amount(
+    entry: &mut Entry,
+    tx: &TxDtls,
+    index: usize,
+) -> Result<(), ParseCamt053Error> {
+    let Some(amt_dtls) = tx.amt_dtls.as_ref() else {
+        return Ok(());
+    };
+    let in_element = |path: &str| {
+        ParseCamt053Error::in_element(format!("NtryDtls/TxDtls[{}]/AmtDtls/{}", index, path))
+    };
+
+    if let Some(instd_amt) = amt_dtls.instd_amt.as_ref() {
+        let money = Money::parse(&instd_amt.amt.value, &instd_amt.amt.ccy)
+            .map_err(|e| in_element("InstdAmt/Amt")(e.into()))?;
+        let booked = entry.inflow.or(entry.outflow).map(|m| m.currency);
+        if booked != Some(money.currency) {
+            entry.original_amount = Some(money);
+        }
+    }
+
+    let ccy_xchg = [
+        ("InstdAmt", &amt_dtls.instd_amt),
+        ("TxAmt", &amt_dtls.tx_amt),
+    ]
+    .into_iter()
+    .find_map(|(path, amt)| {
+        amt.as_ref()
+            .and_then(|a| a.ccy_xchg.as_ref())
+            .map(|c| (path, c))
+    });
+    if let Some((path, ccy_xchg)) = ccy_xchg {
+        let rate = exchange_rate(ccy_xchg).map_err(in_element(&format!("{}/CcyXchg", path)))?;
+        entry.exchange_rate = Some(rate);
+    }
+
+    Ok(())
+}
+
+fn transaction_entry(
+    mut entry: Entry,
+    tx: Option<&TxDtls>,
+    memo: Option<String>,
+    amount: Money,
+    credit_debit_indicator: &CdtDbtIndValue,
+    version: Version,
+) -> Entry {
+    let rltd_pties = tx.and_then(|t| t.rltd_pties.as_ref());
+    let rltd_agts = tx.and_then(|t| t.rltd_agts.as_ref());
+
+    // The counterparty is the creditor of an outgoing payment and the debtor
+    // of an incoming one. Fall back to the other side for banks that only
+    // report one of them.
+    let (counterparty, counterparty_acct, counterparty_agt) = match rltd_pties {
+        Some(r) => {
+            let (cdtr, dbtr) = (
+                (r.cdtr.as_ref(), r.cdtr_acct.as_ref()),
+                (r.dbtr.as_ref(), r.dbtr_acct.as_ref()),
+            );
+            let (cdtr_agt, dbtr_agt) = (
+                rltd_agts.and_then(|a| a.cdtr_agt.as_ref()),
+                rltd_agts.and_then(|a| a.dbtr_agt.as_ref()),
+            );
+            let (first, second, first_agt, second_agt) = match credit_debit_indicator {
+                CdtDbtIndValue::Dbit => (cdtr, dbtr, cdtr_agt, dbtr_agt),
+                CdtDbtIndValue::Crdt => (dbtr, cdtr, dbtr_agt, cdtr_agt),
+            };
+            match first {
+                (None, None) => (second.0, second.1, second_agt),
+                _ => (first.0, first.1, first_agt),
+            }
+        }
+        None => (None, None, None),
+    };
+
+    let payee = counterparty
+        .and_then(|c| c.name(version))
+        .map(str::to_owned);
+    let (payee, memo) = split_payee_memo(payee, memo);
+
+    entry.payee = payee;
+    entry.memo = memo.map(|s| {
+        s.replace('\n', "")
+            .split(' ')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    entry.counterparty_account = counterparty_acct.map(|a| a.id.account().to_owned());
+    entry.counterparty_bic = counterparty_agt.and_then(|a| a.fin_instn_id.bic.to_owned());
+    entry.ultimate_debtor = rltd_pties
+        .and_then(|r| r.ultmt_dbtr.as_ref())
+        .and_then(|u| u.nm.to_owned());
+    entry.ultimate_creditor = rltd_pties
+        .and_then(|r| r.ultmt_cdtr.as_ref())
+        .and_then(|u| u.nm.to_owned());
+
+    match credit_debit_indicator {
+        CdtDbtIndValue::Dbit => entry.outflow = Some(amount),
+        CdtDbtIndValue::Crdt => entry.inflow = Some(amount),
+    }
+
+    entry
+}
+
+/// Converts the parts of a document into statements. Entries that can't be
+/// read or converted fail the whole document, unless `warnings` is given,
+/// in which case they're skipped and collected there with the index of
+/// their statement.
+fn convert_parts(
+    parts: impl IntoIterator<Item = Result<Part, ParseCamt053Error>>,
+    mut warnings: Option<&mut Vec<(usize, ParseCamt053Error)>>,
+) -> Result<Vec<Statement>, ParseCamt053Error> {
+    let mut statements: Vec<Statement> = vec![];
+    let mut report = false;
+    let mut version = Version::default();
+
+    for part in parts {
+        match part? {
+            Part::Statement {
+                stmt,
+                report: is_report,
+                version: stmt_version,
+                location,
+            } => {
+                // Intraday reports aren't final, so their interim balances
+                // aren't reconciled.
+                report = is_report;
+                version = stmt_version;
+                let mut statement = Statement {
+                    account: stmt.acct.id.account().to_owned(),
+                    opening_balance: None,
+                    closing_balance: None,
+                    entries: vec![],
+                };
+                if !report {
+                    let balances = find_balance(&stmt.bal, &["OPBD", "PRCD"])
+                        .and_then(|o| Ok((o, find_balance(&stmt.bal, &["CLBD"])?)));
+                    (statement.opening_balance, statement.closing_balance) =
+                        balances.map_err(|e| e.located(location, None))?;
+                }
+                statements.push(statement);
+            }
+            Part::Entry {
+                ntry,
+                ntry_ref,
+                location,
+            } => {
+                let index = statements.len().saturating_sub(1);
+                let Some(statement) = statements.last_mut() else {
+                    continue;
+                };
+                match ntry.and_then(|n| convert_ntry(&statement.account, *n, report, version)) {
+                    Ok(entries) => statement.entries.extend(entries),
+                    Err(e) => {
+                        let error = e.located(location, ntry_ref);
+                        match warnings.as_mut() {
+                            Some(warnings) => warnings.push((index, error)),
+                            None => return Err(error),
+                        }
+                    }
                 }
             }
         }
+    }
+
+    Ok(statements)
+}
+
+#[derive(Debug)]
+pub struct EntriesContainer {
+    pub statements: Vec<Statement>,
+}
 
-        container
+impl TryFrom<XmlDocument> for EntriesContainer {
+    type Error = ParseCamt053Error;
+
+    fn try_from(value: XmlDocument) -> Result<Self, Self::Error> {
+        Ok(EntriesContainer {
+            statements: convert_parts(document_parts(value)?.into_iter().map(Ok), None)?,
+        })
     }
 }
 
@@ -97,25 +465,123 @@ impl Camt053Parser {
     }
 
     pub fn parse_file(&self, xml_contents: &str) -> Result<Vec<Entry>, ParseCamt053Error> {
-        let camt_053 = self.xml_parser.parse_from_str(xml_contents)?;
+        Ok(self
+            .parse_statements(xml_contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+
+    pub fn parse_statements(
+        &self,
+        xml_contents: &str,
+    ) -> Result<Vec<Statement>, ParseCamt053Error> {
+        convert_parts(
+            self.xml_parser
+                .parse_from_reader(Box::new(xml_contents.as_bytes())),
+            None,
+        )
+    }
 
-        let container: EntriesContainer = camt_053.into();
+    /// Like [`Camt053Parser::parse_statements`], but skips entries that
+    /// can't be read or converted and returns their errors as warnings of
+    /// their statement.
+    pub fn parse_statements_lenient(
+        &self,
+        xml_contents: &str,
+    ) -> Result<Vec<(Statement, Vec<ParseCamt053Error>)>, ParseCamt053Error> {
+        self.read_statements(xml_contents.as_bytes(), true)
+    }
+
+    /// Parses the statements of a document read from `reader` one `Ntry` at
+    /// a time, so only the entries are kept in memory and not the file.
+    pub fn read_statements<'a>(
+        &self,
+        reader: impl BufRead + 'a,
+        lenient: bool,
+    ) -> Result<Vec<(Statement, Vec<ParseCamt053Error>)>, ParseCamt053Error> {
+        let parts = self.xml_parser.parse_from_reader(Box::new(reader));
+        let mut warnings = vec![];
+        let statements = convert_parts(parts, Some(&mut warnings).filter(|_| lenient))?;
+
+        let mut statements = statements
+            .into_iter()
+            .map(|s| (s, vec![]))
+            .collect::<Vec<_>>();
+        for (index, warning) in warnings {
+            statements[index].1.push(warning);
+        }
 
-        Ok(container.entries)
+        Ok(statements)
+    }
+}
+
+impl StatementParser for Camt053Parser {
+    fn name(&self) -> &str {
+        "camt053"
+    }
+
+    fn detect(&self, contents: &[u8]) -> bool {
+        let head = String::from_utf8_lossy(&contents[..contents.len().min(SNIFF_LENGTH)]);
+
+        head.trim_start_matches('\u{feff}')
+            .trim_start()
+            .starts_with('<')
+            && NAMESPACES
+                .iter()
+                .chain(MESSAGES.iter())
+                .any(|n| head.contains(n))
+    }
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError> {
+        Camt053Parser::parse_statements(self, std::str::from_utf8(contents)?)
+            .map_err(ParseStatementError::parser)
+    }
+
+    fn parse_statements_lenient(
+        &self,
+        contents: &[u8],
+    ) -> Result<Vec<(Statement, Vec<String>)>, ParseStatementError> {
+        let statements =
+            Camt053Parser::parse_statements_lenient(self, std::str::from_utf8(contents)?)
+                .map_err(ParseStatementError::parser)?;
+
+        Ok(statements
+            .into_iter()
+            .map(|(s, warnings)| (s, warnings.iter().map(|w| w.to_string()).collect()))
+            .collect())
+    }
+
+    fn read_statements(
+        &self,
+        reader: &mut dyn BufRead,
+        lenient: bool,
+    ) -> Result<Vec<(Statement, Vec<String>)>, ParseStatementError> {
+        let statements = Camt053Parser::read_statements(self, reader, lenient)
+            .map_err(ParseStatementError::parser)?;
+
+        Ok(statements
+            .into_iter()
+            .map(|(s, warnings)| (s, warnings.iter().map(|w| w.to_string()).collect()))
+            .collect())
     }
 }
 
 trait XmlParser {
-    fn parse_from_str(&self, xml_contents: &str) -> Result<XmlDocument, ParseCamt053Error>;
+    fn parse_from_reader<'a>(
+        &self,
+        reader: Box<dyn BufRead + 'a>,
+    ) -> Box<dyn Iterator<Item = Result<Part, ParseCamt053Error>> + 'a>;
 }
 
 struct RealXmlParser {}
 
 impl XmlParser for RealXmlParser {
-    fn parse_from_str(&self, xml_contents: &str) -> Result<XmlDocument, ParseCamt053Error> {
-        let xml_document: XmlDocument = from_str(xml_contents)?;
-
-        Ok(xml_document)
+    fn parse_from_reader<'a>(
+        &self,
+        reader: Box<dyn BufRead + 'a>,
+    ) -> Box<dyn Iterator<Item = Result<Part, ParseCamt053Error>> + 'a> {
+        Box::new(DocumentReader::new(reader))
     }
 }
 
@@ -124,8 +590,14 @@ struct StubbedXmlParser {
 }
 
 impl XmlParser for StubbedXmlParser {
-    fn parse_from_str(&self, _xml_contents: &str) -> Result<XmlDocument, ParseCamt053Error> {
-        Ok(self.xml_document.clone())
+    fn parse_from_reader<'a>(
+        &self,
+        _reader: Box<dyn BufRead + 'a>,
+    ) -> Box<dyn Iterator<Item = Result<Part, ParseCamt053Error>> + 'a> {
+        match document_parts(self.xml_document.clone()) {
+            Ok(parts) => Box::new(parts.into_iter().map(Ok)),
+            Err(e) => Box::new(std::iter::once(Err(e))),
+        }
     }
 }
 
@@ -138,6 +610,7 @@ mod tests {
     #[test]
     fn test_empty_xml_parser() {
         let xml_document = XmlDocument {
+            xmlns: None,
             bk_to_cstmr_stmt: BkToCstmrStmt { items: vec![] },
         };
 
@@ -154,17 +627,17 @@ mod tests {
     #[test]
     fn test_xml_document_has_entries_with_payee() {
         let xml_document = XmlDocument {
+            xmlns: None,
             bk_to_cstmr_stmt: BkToCstmrStmt {
                 items: vec![BkToCstmrStmtItem::Stmt(Stmt {
                     acct: Acct {
-                        id: Id {
-                            iban: "Iban1234account".to_string(),
-                        },
+                        id: Id::Iban("Iban1234account".to_string()),
                     },
+                    bal: vec![],
                     ntry: vec![Ntry::new(
                         "100",
                         CdtDbtIndValue::Crdt,
-                        "19-12-2023",
+                        "2023-12-19",
                         Some("Memo".to_string()),
                         Some("Payee".to_string()),
                     )],
@@ -180,10 +653,10 @@ mod tests {
                 .expect("File to be parsed"),
             vec![Entry::new(
                 "Iban1234account".to_string(),
-                "19-12-2023".to_string(),
+                NaiveDate::from_ymd_opt(2023, 12, 19).expect("date to be valid"),
                 Some("Payee".to_string()),
                 Some("Memo".to_string()),
-                Some("100".to_string()),
+                Some(Money::parse("100", "EUR").expect("amount to be valid")),
                 None,
             )]
         )
@@ -192,17 +665,17 @@ mod tests {
     #[test]
     fn test_xml_document_has_entries_without_payee() {
         let xml_document = XmlDocument {
+            xmlns: None,
             bk_to_cstmr_stmt: BkToCstmrStmt {
                 items: vec![BkToCstmrStmtItem::Stmt(Stmt {
                     acct: Acct {
-                        id: Id {
-                            iban: "Iban1234account".to_string(),
-                        },
+                        id: Id::Iban("Iban1234account".to_string()),
                     },
+                    bal: vec![],
                     ntry: vec![Ntry::new(
                         "100",
                         CdtDbtIndValue::Crdt,
-                        "19-12-2023",
+                        "2023-12-19",
                         Some("Memo".to_string()),
                         None,
                     )],
@@ -218,10 +691,10 @@ mod tests {
                 .expect("File to be parsed"),
             vec![Entry::new(
                 "Iban1234account".to_string(),
-                "19-12-2023".to_string(),
+                NaiveDate::from_ymd_opt(2023, 12, 19).expect("date to be valid"),
                 None,
                 Some("Memo".to_string()),
-                Some("100".to_string()),
+                Some(Money::parse("100", "EUR").expect("amount to be valid")),
                 None,
             )]
         )
@@ -230,17 +703,17 @@ mod tests {
     #[test]
     fn test_xml_document_has_entries_splits_memo_gt() {
         let xml_document = XmlDocument {
+            xmlns: None,
             bk_to_cstmr_stmt: BkToCstmrStmt {
                 items: vec![BkToCstmrStmtItem::Stmt(Stmt {
                     acct: Acct {
-                        id: Id {
-                            iban: "Iban1234account".to_string(),
-                        },
+                        id: Id::Iban("Iban1234account".to_string()),
                     },
+                    bal: vec![],
                     ntry: vec![Ntry::new(
                         "100",
                         CdtDbtIndValue::Crdt,
-                        "19-12-2023",
+                        "2023-12-19",
                         Some("Payee > Memo".to_string()),
                         None,
                     )],
@@ -256,12 +729,464 @@ mod tests {
                 .expect("File to be parsed"),
             vec![Entry::new(
                 "Iban1234account".to_string(),
-                "19-12-2023".to_string(),
+                NaiveDate::from_ymd_opt(2023, 12, 19).expect("date to be valid"),
                 Some("Payee".to_string()),
                 Some("Memo".to_string()),
-                Some("100".to_string()),
+                Some(Money::parse("100", "EUR").expect("amount to be valid")),
                 None,
             )]
         )
     }
+
+    #[test]
+    fn test_xml_document_with_malformed_amount() {
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![Ntry::new(
+                        "1.000,00",
+                        CdtDbtIndValue::Crdt,
+                        "2023-12-19",
+                        None,
+                        None,
+                    )],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        assert!(matches!(
+            camt_053_parser.parse_file("<xml><is><mocked>"),
+            Err(ParseCamt053Error::Located { location, source, .. })
+                if location.path == "Document/BkToCstmrStmt/Stmt[0]/Ntry[0]/Amt"
+                    && matches!(*source, ParseCamt053Error::AmountError(MoneyError::InvalidAmount(_)))
+        ))
+    }
+
+    #[test]
+    fn test_xml_document_with_invalid_date() {
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![Ntry::new(
+                        "100",
+                        CdtDbtIndValue::Crdt,
+                        "2023-02-30",
+                        None,
+                        None,
+                    )],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        assert!(matches!(
+            camt_053_parser.parse_file("<xml><is><mocked>"),
+            Err(ParseCamt053Error::Located { location, source, .. })
+                if location.path == "Document/BkToCstmrStmt/Stmt[0]/Ntry[0]/BookgDt"
+                    && matches!(&*source, ParseCamt053Error::DateError(date) if date == "2023-02-30")
+        ))
+    }
+
+    const BROKEN_ENTRY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+      <Ntry><NtryRef>A1</NtryRef><Amt Ccy="EUR">10.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt></Ntry>
+      <Ntry><NtryRef>A2</NtryRef><Amt Ccy="EUR">10.00</Amt><CdtDbtInd>BOTH</CdtDbtInd><BookgDt><Dt>2023-04-02</Dt></BookgDt></Ntry>
+      <Ntry><NtryRef>A3</NtryRef><Amt Ccy="EUR">10.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2023-04-03</Dt></BookgDt>
+        <NtryDtls><TxDtls><AmtDtls><TxAmt><Amt Ccy="EUR">ten</Amt></TxAmt></AmtDtls></TxDtls></NtryDtls>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn test_broken_entry_is_located() {
+        let camt_053_parser = Camt053Parser::create();
+
+        let error = camt_053_parser
+            .parse_statements(BROKEN_ENTRY)
+            .expect_err("entry to be rejected");
+
+        let ParseCamt053Error::Located {
+            location, ntry_ref, ..
+        } = &error
+        else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert_eq!(
+            location.path,
+            "Document/BkToCstmrStmt/Stmt[0]/Ntry[1]/CdtDbtInd"
+        );
+        assert_eq!(
+            location.position,
+            Some(Position {
+                offset: 332,
+                line: 7,
+                column: 7
+            })
+        );
+        assert_eq!(ntry_ref.as_deref(), Some("A2"));
+        assert!(error.to_string().ends_with(
+            "at Document/BkToCstmrStmt/Stmt[0]/Ntry[1]/CdtDbtInd (line 7, column 7, byte 332), NtryRef A2"
+        ));
+    }
+
+    #[test]
+    fn test_broken_child_element_is_located() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt>
+            <Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+            <Ntry><Amt Ccy="EUR">1.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt>
+                <NtryDtls><TxDtls/><TxDtls><RltdPties><CdtrAcct><Id><BBAN>1</BBAN></Id></CdtrAcct></RltdPties></TxDtls></NtryDtls>
+            </Ntry>
+        </Stmt></BkToCstmrStmt></Document>"#;
+
+        let error = Camt053Parser::create()
+            .parse_statements(xml)
+            .expect_err("entry to be rejected");
+
+        assert!(matches!(
+            error,
+            ParseCamt053Error::Located { location, .. }
+                if location.path
+                    == "Document/BkToCstmrStmt/Stmt[0]/Ntry[0]/NtryDtls/TxDtls[1]/RltdPties/CdtrAcct/Id"
+        ));
+    }
+
+    #[test]
+    fn test_lenient_skips_broken_entries() {
+        let camt_053_parser = Camt053Parser::create();
+
+        let statements = camt_053_parser
+            .parse_statements_lenient(BROKEN_ENTRY)
+            .expect("statements to be parsed");
+
+        let (statement, warnings) = &statements[0];
+        assert_eq!(statement.entries.len(), 1);
+        assert_eq!(statement.entries[0].reference, Some("A1".to_string()));
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(
+            &warnings[1],
+            ParseCamt053Error::Located { location, ntry_ref: Some(r), .. }
+                if r == "A3"
+                    && location.path
+                        == "Document/BkToCstmrStmt/Stmt[0]/Ntry[2]/NtryDtls/TxDtls[0]/AmtDtls/TxAmt/Amt"
+                    && location.position.as_ref().map(|p| p.line) == Some(8)
+        ));
+    }
+
+    #[test]
+    fn test_lenient_warnings_belong_to_their_statement() {
+        let xml = r#"<Document><BkToCstmrStmt>
+            <Stmt><Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+                <Ntry><Amt Ccy="EUR">1.00</Amt><CdtDbtInd>BOTH</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt></Ntry>
+            </Stmt>
+            <Stmt><Acct><Id><IBAN>NL86ASNB8830237086</IBAN></Id></Acct>
+                <Ntry><Amt Ccy="EUR">1.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt></Ntry>
+            </Stmt>
+        </BkToCstmrStmt></Document>"#;
+
+        let statements = Camt053Parser::create()
+            .parse_statements_lenient(xml)
+            .expect("statements to be parsed");
+
+        let warnings = statements
+            .iter()
+            .map(|(s, w)| (s.account.as_str(), w.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![("NL00BANK0000000000", 1), ("NL86ASNB8830237086", 0)]
+        );
+    }
+
+    #[test]
+    fn test_xml_document_has_statement_balances() {
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![
+                        Bal::new("OPBD", "50", CdtDbtIndValue::Dbit, "2023-12-01"),
+                        Bal::new("CLBD", "50", CdtDbtIndValue::Crdt, "2023-12-31"),
+                    ],
+                    ntry: vec![Ntry::new(
+                        "100",
+                        CdtDbtIndValue::Crdt,
+                        "2023-12-19",
+                        None,
+                        None,
+                    )],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        let statements = camt_053_parser
+            .parse_statements("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(
+            statements[0].opening_balance,
+            Some(Money::parse("-50", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(
+            statements[0].closing_balance,
+            Some(Money::parse("50", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(statements[0].reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn test_xml_document_has_reversal_entry() {
+        let mut ntry = Ntry::new("100", CdtDbtIndValue::Crdt, "2023-12-19", None, None);
+        ntry.rvsl_ind = Some(true);
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![ntry],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        let entries = camt_053_parser
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert!(entries[0].reversal);
+        assert_eq!(
+            entries[0].inflow,
+            Some(Money::parse("100", "EUR").expect("amount to be valid"))
+        );
+    }
+
+    #[test]
+    fn test_xml_document_has_pending_entry() {
+        let mut ntry = Ntry::new("100", CdtDbtIndValue::Crdt, "2023-12-19", None, None);
+        ntry.sts = Some(Sts::new(StsValue::Pdng));
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![
+                        ntry,
+                        Ntry::new("100", CdtDbtIndValue::Crdt, "2023-12-19", None, None),
+                    ],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        let entries = camt_053_parser
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(entries[0].cleared, ClearedState::Uncleared);
+        assert_eq!(entries[1].cleared, ClearedState::Cleared);
+    }
+
+    #[test]
+    fn test_xml_document_version_08_status_code() {
+        let mut ntry = Ntry::new("100", CdtDbtIndValue::Crdt, "2023-12-19", None, None);
+        ntry.sts = Some(Sts {
+            content: None,
+            cd: Some(StsValue::Pdng),
+        });
+        let xml_document = XmlDocument {
+            xmlns: Some("urn:iso:std:iso:20022:tech:xsd:camt.053.001.08".to_string()),
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![ntry],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        let entries = camt_053_parser
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(entries[0].cleared, ClearedState::Uncleared);
+    }
+
+    #[test]
+    fn test_xml_document_with_unsupported_namespace() {
+        let xml_document = XmlDocument {
+            xmlns: Some("urn:iso:std:iso:20022:tech:xsd:camt.053.001.99".to_string()),
+            bk_to_cstmr_stmt: BkToCstmrStmt { items: vec![] },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        assert!(matches!(
+            camt_053_parser.parse_file("<xml><is><mocked>"),
+            Err(ParseCamt053Error::Located { source, .. })
+                if matches!(*source, ParseCamt053Error::UnsupportedNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_xml_document_has_uncleared_report_entries() {
+        let mut ntry = Ntry::new("100", CdtDbtIndValue::Crdt, "2023-12-19", None, None);
+        ntry.bookg_dt = None;
+        ntry.val_dt = Some(BookgDt {
+            dt: None,
+            dt_tm: Some("2023-12-20T10:15:00+01:00".to_string()),
+        });
+        let xml_document = XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Rpt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![Bal::new("OPBD", "50", CdtDbtIndValue::Crdt, "2023-12-19")],
+                    ntry: vec![ntry],
+                })],
+            },
+        };
+
+        let camt_053_parser = Camt053Parser::create_nullable(xml_document);
+
+        let statements = camt_053_parser
+            .parse_statements("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(statements[0].opening_balance, None);
+        assert_eq!(statements[0].entries[0].cleared, ClearedState::Uncleared);
+        assert_eq!(
+            statements[0].entries[0].date,
+            NaiveDate::from_ymd_opt(2023, 12, 20).expect("date to be valid")
+        );
+    }
+
+    fn batch_document(tx_amounts: [&str; 2]) -> XmlDocument {
+        let mut ntry = Ntry::new("300", CdtDbtIndValue::Dbit, "2023-12-19", None, None);
+        ntry.ntry_ref = Some("REF".to_string());
+        ntry.addtl_ntry_inf = Some("Salary run".to_string());
+        ntry.ntry_dtls.tx_dtls = vec![
+            TxDtls::new(
+                Some(tx_amounts[0]),
+                Some("Salary December".to_string()),
+                Some("Alice".to_string()),
+            ),
+            TxDtls::new(
+                Some(tx_amounts[1]),
+                Some("Salary December".to_string()),
+                Some("Bob".to_string()),
+            ),
+        ];
+
+        XmlDocument {
+            xmlns: None,
+            bk_to_cstmr_stmt: BkToCstmrStmt {
+                items: vec![BkToCstmrStmtItem::Stmt(Stmt {
+                    acct: Acct {
+                        id: Id::Iban("Iban1234account".to_string()),
+                    },
+                    bal: vec![],
+                    ntry: vec![ntry],
+                })],
+            },
+        }
+    }
+
+    #[test]
+    fn test_xml_document_has_batch_entry() {
+        let camt_053_parser = Camt053Parser::create_nullable(batch_document(["100", "200"]));
+
+        let entries = camt_053_parser
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].payee, Some("Alice".to_string()));
+        assert_eq!(entries[0].memo, Some("Salary December".to_string()));
+        assert_eq!(
+            entries[0].outflow,
+            Some(Money::parse("100", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(entries[0].reference, Some("REF/1".to_string()));
+        assert_eq!(entries[0].split, Some(Split { index: 1, count: 2 }));
+        assert_eq!(entries[1].payee, Some("Bob".to_string()));
+        assert_eq!(
+            entries[1].outflow,
+            Some(Money::parse("200", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(entries[1].split, Some(Split { index: 2, count: 2 }));
+    }
+
+    #[test]
+    fn test_xml_document_has_batch_entry_not_adding_up() {
+        let camt_053_parser = Camt053Parser::create_nullable(batch_document(["100", "150"]));
+
+        let entries = camt_053_parser
+            .parse_file("<xml><is><mocked>")
+            .expect("File to be parsed");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].payee, None);
+        assert_eq!(entries[0].memo, Some("Salary run".to_string()));
+        assert_eq!(
+            entries[0].outflow,
+            Some(Money::parse("300", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(entries[0].reference, Some("REF".to_string()));
+        assert_eq!(entries[0].split, None);
+    }
+
+    #[test]
+    fn test_exchange_rate_per_unit_currency() {
+        let ccy_xchg = |unit_ccy: Option<&str>, rate: &str| CcyXchg {
+            src_ccy: "USD".to_string(),
+            trgt_ccy: "EUR".to_string(),
+            unit_ccy: unit_ccy.map(|u| u.to_string()),
+            xchg_rate: rate.to_string(),
+        };
+        let usd: Currency = "USD".parse().expect("currency to be valid");
+
+        let rate = exchange_rate(&ccy_xchg(Some("EUR"), "1.086")).expect("rate to be valid");
+        assert_eq!((rate.source, rate.target), (Currency::EUR, usd));
+        let rate = exchange_rate(&ccy_xchg(None, "0.9208")).expect("rate to be valid");
+        assert_eq!((rate.source, rate.target), (usd, Currency::EUR));
+        assert!(matches!(
+            exchange_rate(&ccy_xchg(None, "0")),
+            Err(ParseCamt053Error::ExchangeRateError(r)) if r == "0"
+        ));
+    }
 }
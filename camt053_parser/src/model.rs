@@ -1,21 +1,78 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+
+use crate::version::Version;
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub enum BkToCstmrStmtItem {
     Stmt(Stmt),
+    /// A camt.052 intraday account report.
+    Rpt(Stmt),
+    /// A camt.054 debit/credit notification.
+    Ntfctn(Stmt),
     #[serde(other)]
     Other,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
-pub struct Id {
+#[serde(rename_all = "PascalCase")]
+pub struct SchmeNm {
+    pub cd: Option<String>,
+    pub prtry: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Othr {
+    pub id: String,
+    pub schme_nm: Option<SchmeNm>,
+}
+
+/// The identifier of an account. Credit cards, savings and non-SEPA
+/// accounts use an `Othr` identifier instead of an IBAN.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub enum Id {
     #[serde(rename = "IBAN")]
-    pub iban: String,
+    Iban(String),
+    Othr(Othr),
+}
+
+impl Id {
+    /// The IBAN, or the other identifier without its scheme.
+    pub fn account(&self) -> &str {
+        match self {
+            Id::Iban(iban) => iban,
+            Id::Othr(othr) => &othr.id,
+        }
+    }
+
+    /// The scheme code or proprietary name of an other identifier, e.g.
+    /// `BBAN`.
+    pub fn scheme(&self) -> Option<&str> {
+        match self {
+            Id::Iban(_) => None,
+            Id::Othr(othr) => othr
+                .schme_nm
+                .as_ref()
+                .and_then(|s| s.cd.as_deref().or(s.prtry.as_deref())),
+        }
+    }
+}
+
+/// Deserializes the choice inside an `Id` element.
+fn id_choice<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Id, D::Error> {
+    #[derive(Deserialize)]
+    struct IdElement {
+        #[serde(rename = "$value")]
+        id: Id,
+    }
+
+    Ok(IdElement::deserialize(deserializer)?.id)
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Acct {
+    #[serde(deserialize_with = "id_choice")]
     pub id: Id,
 }
 
@@ -27,16 +84,83 @@ pub enum CdtDbtIndValue {
     Crdt,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Amt {
+    #[serde(rename = "@Ccy")]
+    pub ccy: String,
+    #[serde(rename = "$text")]
+    pub value: String,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct CdtDbtInd {
     #[serde(rename = "$text")]
     pub content: CdtDbtIndValue,
 }
 
+/// Entry status. 001.08 takes it from an external code list, so codes that
+/// aren't known here are read as [`StsValue::Other`].
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub enum StsValue {
+    #[serde(rename = "BOOK")]
+    Book,
+    #[serde(rename = "PDNG")]
+    Pdng,
+    #[serde(rename = "INFO")]
+    Info,
+    /// Booked at a future date.
+    #[serde(rename = "FUTR")]
+    Futr,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Sts {
+    #[serde(rename = "$text")]
+    pub content: Option<StsValue>,
+    #[serde(rename = "Cd")]
+    pub cd: Option<StsValue>,
+}
+
+impl Sts {
+    pub fn new(content: StsValue) -> Self {
+        Sts {
+            content: Some(content),
+            cd: None,
+        }
+    }
+
+    /// The status, which 001.08 wraps in a `Cd`.
+    pub fn value(&self, version: Version) -> Option<&StsValue> {
+        match version {
+            Version::V02 | Version::V04 => self.content.as_ref(),
+            Version::V08 => self.cd.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct BookgDt {
-    pub dt: String,
+    pub dt: Option<String>,
+    pub dt_tm: Option<String>,
+}
+
+impl BookgDt {
+    pub fn new(date: &str) -> Self {
+        BookgDt {
+            dt: Some(date.to_string()),
+            dt_tm: None,
+        }
+    }
+
+    /// The date, or the date part of an ISO date time.
+    pub fn date(&self) -> Option<&str> {
+        self.dt
+            .as_deref()
+            .or(self.dt_tm.as_deref().and_then(|d| d.get(..10)))
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
@@ -46,39 +170,208 @@ pub struct RmtInf {
     pub ustrd: Vec<String>,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Pty {
+    pub nm: Option<String>,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Cdtr {
-    pub nm: String,
+    pub nm: Option<String>,
+    pub pty: Option<Pty>,
+}
+
+impl Cdtr {
+    pub fn new(name: String) -> Self {
+        Cdtr {
+            nm: Some(name),
+            pty: None,
+        }
+    }
+
+    /// The name of a debtor or creditor, which 001.08 wraps in a `Pty`.
+    pub fn name(&self, version: Version) -> Option<&str> {
+        match version {
+            Version::V02 | Version::V04 => self.nm.as_deref(),
+            Version::V08 => self.pty.as_ref().and_then(|p| p.nm.as_deref()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct RltdPties {
     pub cdtr: Option<Cdtr>,
+    pub cdtr_acct: Option<Acct>,
     pub dbtr: Option<Cdtr>,
+    pub dbtr_acct: Option<Acct>,
+    pub ultmt_dbtr: Option<Cdtr>,
+    pub ultmt_cdtr: Option<Cdtr>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct FinInstnId {
+    #[serde(rename = "BIC", alias = "BICFI")]
+    pub bic: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Agt {
+    pub fin_instn_id: FinInstnId,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct RltdAgts {
+    pub cdtr_agt: Option<Agt>,
+    pub dbtr_agt: Option<Agt>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Fmly {
+    pub cd: String,
+    pub sub_fmly_cd: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Domn {
+    pub cd: String,
+    pub fmly: Fmly,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Prtry {
+    pub cd: String,
+    pub issr: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct BkTxCd {
+    pub domn: Option<Domn>,
+    pub prtry: Option<Prtry>,
+}
+
+impl BkTxCd {
+    /// The proprietary code when the bank provides one, otherwise the ISO
+    /// domain code as `DOMAIN-FAMILY-SUBFAMILY`.
+    pub fn code(&self) -> Option<String> {
+        self.prtry.as_ref().map(|p| p.cd.to_owned()).or(self
+            .domn
+            .as_ref()
+            .map(|d| format!("{}-{}-{}", d.cd, d.fmly.cd, d.fmly.sub_fmly_cd)))
+    }
+}
+
+/// The exchange rate of a payment in another currency. The rate is
+/// expressed per unit of `UnitCcy`, which defaults to the source currency.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct CcyXchg {
+    pub src_ccy: String,
+    pub trgt_ccy: String,
+    pub unit_ccy: Option<String>,
+    pub xchg_rate: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct AmtAndCcyXchg {
+    pub amt: Amt,
+    pub ccy_xchg: Option<CcyXchg>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct AmtDtls {
+    /// The amount as instructed by the payer, before currency exchange.
+    pub instd_amt: Option<AmtAndCcyXchg>,
+    pub tx_amt: Option<AmtAndCcyXchg>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct TxDtls {
+    pub amt: Option<Amt>,
+    pub amt_dtls: Option<AmtDtls>,
     pub rmt_inf: Option<RmtInf>,
     pub rltd_pties: Option<RltdPties>,
+    pub rltd_agts: Option<RltdAgts>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+impl TxDtls {
+    pub fn new(amount: Option<&str>, memo: Option<String>, payee: Option<String>) -> Self {
+        TxDtls {
+            amt: None,
+            amt_dtls: amount.map(|a| AmtDtls {
+                instd_amt: None,
+                tx_amt: Some(AmtAndCcyXchg {
+                    amt: Amt {
+                        ccy: "EUR".to_string(),
+                        value: a.to_string(),
+                    },
+                    ccy_xchg: None,
+                }),
+            }),
+            rmt_inf: memo.map(|m| RmtInf { ustrd: vec![m] }),
+            rltd_pties: payee.map(|p| RltdPties {
+                cdtr: Some(Cdtr::new(p)),
+                cdtr_acct: None,
+                dbtr: None,
+                dbtr_acct: None,
+                ultmt_dbtr: None,
+                ultmt_cdtr: None,
+            }),
+            rltd_agts: None,
+        }
+    }
+
+    /// The transaction amount with its path in the `TxDtls`. Since 001.04
+    /// it has an `Amt` of its own, which older versions only have in the
+    /// `AmtDtls`.
+    pub fn tx_amt(&self, version: Version) -> Option<(&'static str, &Amt)> {
+        let amt_dtls = || {
+            self.amt_dtls
+                .as_ref()
+                .and_then(|a| a.tx_amt.as_ref())
+                .map(|t| ("AmtDtls/TxAmt/Amt", &t.amt))
+        };
+
+        match version {
+            Version::V02 => amt_dtls(),
+            Version::V04 | Version::V08 => self.amt.as_ref().map(|a| ("Amt", a)).or_else(amt_dtls),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
 #[serde(rename_all = "PascalCase")]
 pub struct NtryDtls {
-    pub tx_dtls: TxDtls,
+    #[serde(default)]
+    pub tx_dtls: Vec<TxDtls>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Ntry {
-    pub amt: String,
+    pub ntry_ref: Option<String>,
+    pub amt: Amt,
     pub cdt_dbt_ind: CdtDbtInd,
-    pub bookg_dt: BookgDt,
+    pub rvsl_ind: Option<bool>,
+    pub sts: Option<Sts>,
+    pub bookg_dt: Option<BookgDt>,
+    pub val_dt: Option<BookgDt>,
+    pub bk_tx_cd: Option<BkTxCd>,
+    #[serde(default)]
     pub ntry_dtls: NtryDtls,
+    pub acct_svcr_ref: Option<String>,
     pub addtl_ntry_inf: Option<String>,
 }
 
@@ -90,35 +383,88 @@ impl Ntry {
         memo: Option<String>,
         payee: Option<String>,
     ) -> Self {
-        let rltd_pties = payee.map(|p| RltdPties {
-            cdtr: Some(Cdtr { nm: p }),
-            dbtr: None,
-        });
-
         Ntry {
-            amt: amount.to_string(),
+            ntry_ref: None,
+            amt: Amt {
+                ccy: "EUR".to_string(),
+                value: amount.to_string(),
+            },
             cdt_dbt_ind: CdtDbtInd {
                 content: credit_debit_indicator,
             },
-            bookg_dt: BookgDt {
-                dt: date.to_string(),
-            },
+            rvsl_ind: None,
+            sts: Some(Sts::new(StsValue::Book)),
+            bookg_dt: Some(BookgDt::new(date)),
+            val_dt: None,
+            bk_tx_cd: None,
             ntry_dtls: NtryDtls {
-                tx_dtls: TxDtls {
-                    rmt_inf: memo.clone().map(|m| RmtInf { ustrd: vec![m] }),
-                    rltd_pties,
-                },
+                tx_dtls: vec![TxDtls::new(None, memo.clone(), payee)],
             },
+            acct_svcr_ref: None,
             addtl_ntry_inf: memo,
         }
     }
 }
 
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct CdOrPrtry {
+    pub cd: Option<String>,
+    pub prtry: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct BalTp {
+    pub cd_or_prtry: CdOrPrtry,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Bal {
+    pub tp: BalTp,
+    pub amt: Amt,
+    pub cdt_dbt_ind: CdtDbtInd,
+    pub dt: BookgDt,
+}
+
+impl Bal {
+    pub fn new(
+        code: &str,
+        amount: &str,
+        credit_debit_indicator: CdtDbtIndValue,
+        date: &str,
+    ) -> Self {
+        Bal {
+            tp: BalTp {
+                cd_or_prtry: CdOrPrtry {
+                    cd: Some(code.to_string()),
+                    prtry: None,
+                },
+            },
+            amt: Amt {
+                ccy: "EUR".to_string(),
+                value: amount.to_string(),
+            },
+            cdt_dbt_ind: CdtDbtInd {
+                content: credit_debit_indicator,
+            },
+            dt: BookgDt::new(date),
+        }
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.tp.cd_or_prtry.cd.as_deref()
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Stmt {
     pub acct: Acct,
     #[serde(default)]
+    pub bal: Vec<Bal>,
+    #[serde(default)]
     pub ntry: Vec<Ntry>,
 }
 
@@ -128,8 +474,49 @@ pub struct BkToCstmrStmt {
     pub items: Vec<BkToCstmrStmtItem>,
 }
 
+/// A camt.053 statement. camt.052 reports and camt.054 notifications share
+/// its structure, so they are read into the same message.
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct XmlDocument {
+    /// The namespace, which holds the schema version.
+    #[serde(rename = "@xmlns")]
+    pub xmlns: Option<String>,
+    #[serde(alias = "BkToCstmrAcctRpt", alias = "BkToCstmrDbtCdtNtfctn")]
     pub bk_to_cstmr_stmt: BkToCstmrStmt,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_account_identifier_choice() {
+        let iban: Acct =
+            quick_xml::de::from_str("<Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>")
+                .expect("account to be read");
+        let othr: Acct = quick_xml::de::from_str(
+            "<Acct><Id><Othr><Id>5412750000001234</Id><SchmeNm><Prtry>CCARD</Prtry></SchmeNm></Othr></Id><Ccy>EUR</Ccy></Acct>",
+        )
+        .expect("account to be read");
+
+        assert_eq!(iban.id, Id::Iban("NL00BANK0000000000".to_string()));
+        assert_eq!(iban.id.scheme(), None);
+        assert_eq!(othr.id.account(), "5412750000001234");
+        assert_eq!(othr.id.scheme(), Some("CCARD"));
+    }
+
+    #[test]
+    fn reads_external_status_codes() {
+        let status = |xml: &str| {
+            quick_xml::de::from_str::<Sts>(xml)
+                .expect("status to be read")
+                .value(Version::V08)
+                .cloned()
+        };
+
+        assert_eq!(status("<Sts><Cd>BOOK</Cd></Sts>"), Some(StsValue::Book));
+        assert_eq!(status("<Sts><Cd>FUTR</Cd></Sts>"), Some(StsValue::Futr));
+        assert_eq!(status("<Sts><Cd>XXXX</Cd></Sts>"), Some(StsValue::Other));
+    }
+}
@@ -0,0 +1,476 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    io::{self, BufRead, Read},
+};
+
+use quick_xml::{
+    errors::IllFormedError,
+    events::{BytesStart, Event},
+    Reader, Writer,
+};
+use serde::Deserialize;
+use serde_path_to_error::Segment;
+
+use crate::{
+    model::{BkToCstmrStmtItem, Ntry, Stmt, XmlDocument},
+    version::Version,
+    ParseCamt053Error,
+};
+
+/// Deserializes an `Ntry`. Errors name the element that failed within it,
+/// e.g. `NtryDtls/TxDtls[0]/Amt`.
+fn deserialize_ntry(xml: &str) -> Result<Ntry, ParseCamt053Error> {
+    let mut deserializer = quick_xml::de::Deserializer::from_str(xml);
+
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let mut path = String::new();
+        for segment in e.path().iter() {
+            match segment {
+                Segment::Seq { index } => path.push_str(&format!("[{}]", index)),
+                Segment::Map { key } if key.starts_with('$') => {}
+                Segment::Map { key } => path.push_str(&format!("/{}", key)),
+                Segment::Enum { variant } => path.push_str(&format!("/{}", variant)),
+                Segment::Unknown => {}
+            }
+        }
+
+        let error = ParseCamt053Error::ParseError(e.into_inner());
+        match path.strip_prefix('/') {
+            Some(element) => ParseCamt053Error::in_element(element)(error),
+            None => error,
+        }
+    })
+}
+
+/// Elements holding the account and entries of a statement, report or
+/// notification.
+const ITEMS: [&str; 3] = ["Stmt", "Rpt", "Ntfctn"];
+
+/// Where an element starts in the file. Columns count bytes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Position {
+    pub offset: u64,
+    pub line: u64,
+    pub column: u64,
+}
+
+/// The path of an element, e.g. `Document/BkToCstmrStmt/Stmt[0]/Ntry[17]`,
+/// and its position when it was read from a file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Location {
+    pub path: String,
+    pub position: Option<Position>,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(position) = self.position.as_ref() {
+            write!(
+                f,
+                " (line {}, column {}, byte {})",
+                position.line, position.column, position.offset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A piece of a camt document, in document order.
+pub(crate) enum Part {
+    /// A statement, report or notification without its entries.
+    Statement {
+        stmt: Stmt,
+        report: bool,
+        version: Version,
+        location: Location,
+    },
+    /// An entry of the last statement. Entries that can't be read are kept as
+    /// errors, so they can be skipped.
+    Entry {
+        ntry: Result<Box<Ntry>, ParseCamt053Error>,
+        ntry_ref: Option<String>,
+        location: Location,
+    },
+}
+
+/// The parts of a document that was deserialized as a whole. The paths use
+/// the element names of a camt.053 statement.
+pub(crate) fn document_parts(document: XmlDocument) -> Result<Vec<Part>, ParseCamt053Error> {
+    let version = Version::from_optional_namespace(document.xmlns.as_deref()).map_err(|e| {
+        e.located(
+            Location {
+                path: "Document".to_owned(),
+                position: None,
+            },
+            None,
+        )
+    })?;
+    let mut parts = vec![];
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for item in document.bk_to_cstmr_stmt.items {
+        let (name, mut stmt) = match item {
+            BkToCstmrStmtItem::Stmt(stmt) => ("Stmt", stmt),
+            BkToCstmrStmtItem::Rpt(stmt) => ("Rpt", stmt),
+            BkToCstmrStmtItem::Ntfctn(stmt) => ("Ntfctn", stmt),
+            BkToCstmrStmtItem::Other => continue,
+        };
+        let count = counts.entry(name).or_default();
+        let path = format!("Document/BkToCstmrStmt/{}[{}]", name, count);
+        *count += 1;
+
+        let entries = std::mem::take(&mut stmt.ntry);
+        parts.push(Part::Statement {
+            stmt,
+            report: name == "Rpt",
+            version,
+            location: Location {
+                path: path.to_owned(),
+                position: None,
+            },
+        });
+        for (index, ntry) in entries.into_iter().enumerate() {
+            parts.push(Part::Entry {
+                ntry_ref: ntry.ntry_ref.to_owned(),
+                ntry: Ok(Box::new(ntry)),
+                location: Location {
+                    path: format!("{}/Ntry[{}]", path, index),
+                    position: None,
+                },
+            });
+        }
+    }
+
+    Ok(parts)
+}
+
+/// The lines read so far, to turn byte offsets into positions.
+#[derive(Default)]
+struct Lines {
+    offset: u64,
+    count: u64,
+    line_start: u64,
+}
+
+impl Lines {
+    fn read(&mut self, bytes: &[u8]) {
+        for (index, byte) in bytes.iter().enumerate() {
+            if *byte == b'\n' {
+                self.count += 1;
+                self.line_start = self.offset + index as u64 + 1;
+            }
+        }
+        self.offset += bytes.len() as u64;
+    }
+
+    /// The position of an offset on the current line.
+    fn position(&self, offset: u64) -> Position {
+        Position {
+            offset,
+            line: self.count + 1,
+            column: offset.saturating_sub(self.line_start) + 1,
+        }
+    }
+}
+
+struct LineCounter<R> {
+    inner: R,
+    lines: Lines,
+}
+
+impl<R: Read> Read for LineCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.lines.read(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for LineCounter<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // The consumed bytes are still buffered.
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.lines.read(&buf[..amount.min(buf.len())]);
+        }
+        self.inner.consume(amount);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NtryRef {
+    ntry_ref: Option<String>,
+}
+
+/// An element that is being read, with the number of children per name to
+/// index repeated elements.
+struct Frame {
+    name: String,
+    segment: String,
+    counts: HashMap<String, usize>,
+}
+
+/// The statement that is being read until its first entry, whose events
+/// are copied to deserialize it on its own.
+struct Header {
+    writer: Writer<Vec<u8>>,
+    depth: usize,
+    report: bool,
+    location: Location,
+}
+
+/// The schema version declared by the namespace of the root element, which
+/// may be the default namespace or the one of its prefix.
+fn root_version(start: &BytesStart) -> Result<Version, ParseCamt053Error> {
+    let key = match start.name().prefix() {
+        Some(prefix) => format!("xmlns:{}", String::from_utf8_lossy(prefix.as_ref())),
+        None => "xmlns".to_owned(),
+    };
+    let namespace = start
+        .try_get_attribute(key.as_str())?
+        .map(|a| a.unescape_value().map(|v| v.into_owned()))
+        .transpose()?;
+
+    Version::from_optional_namespace(namespace.as_deref())
+}
+
+/// Reads a camt document event by event. Statements are deserialized
+/// without their entries, and every entry on its own, so memory use doesn't
+/// grow with the size of the file and errors can be located.
+pub(crate) struct DocumentReader<R: BufRead> {
+    reader: Reader<LineCounter<R>>,
+    buf: Vec<u8>,
+    frames: Vec<Frame>,
+    item_depth: Option<usize>,
+    header: Option<Header>,
+    version: Version,
+    parts: VecDeque<Part>,
+    done: bool,
+}
+
+impl<R: BufRead> DocumentReader<R> {
+    pub fn new(reader: R) -> Self {
+        DocumentReader {
+            reader: Reader::from_reader(LineCounter {
+                inner: reader,
+                lines: Lines::default(),
+            }),
+            buf: vec![],
+            frames: vec![],
+            item_depth: None,
+            header: None,
+            version: Version::default(),
+            parts: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    fn path(&self) -> String {
+        self.frames
+            .iter()
+            .map(|f| f.segment.as_str())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn location(&self, offset: u64) -> Location {
+        Location {
+            path: self.path(),
+            position: Some(self.reader.get_ref().lines.position(offset)),
+        }
+    }
+
+    /// Opens an element, indexing statements and entries by their position
+    /// in the parent.
+    fn open(&mut self, name: String) {
+        let segment = match self.frames.last_mut() {
+            Some(parent) if ITEMS.contains(&name.as_str()) || name == "Ntry" => {
+                let count = parent.counts.entry(name.to_owned()).or_default();
+                *count += 1;
+                format!("{}[{}]", name, *count - 1)
+            }
+            _ => name.to_owned(),
+        };
+        self.frames.push(Frame {
+            name,
+            segment,
+            counts: HashMap::new(),
+        });
+    }
+
+    /// Deserializes the statement read so far, if it hasn't been yet.
+    fn finish_header(&mut self) -> Result<(), ParseCamt053Error> {
+        let Some(mut header) = self.header.take() else {
+            return Ok(());
+        };
+
+        let name = &self.frames[header.depth].name;
+        header
+            .writer
+            .write_event(Event::End(quick_xml::events::BytesEnd::new(name)))?;
+        let stmt = String::from_utf8(header.writer.into_inner())
+            .map_err(|e| ParseCamt053Error::XmlError(e.into()))
+            .and_then(|xml| Ok(quick_xml::de::from_str::<Stmt>(&xml)?))
+            .map_err(|e| e.located(header.location.to_owned(), None))?;
+
+        self.parts.push_back(Part::Statement {
+            stmt,
+            report: header.report,
+            version: self.version,
+            location: header.location,
+        });
+        Ok(())
+    }
+
+    /// Copies the events of the `Ntry` that just started into a document of
+    /// its own and deserializes it.
+    fn read_ntry(&mut self, start: BytesStart<'static>) -> Result<Part, ParseCamt053Error> {
+        let offset = self.reader.buffer_position() - start.len() as u64 - 2;
+        self.open("Ntry".to_owned());
+        let location = self.location(offset);
+
+        let mut writer = Writer::new(vec![]);
+        writer.write_event(Event::Start(start))?;
+        let mut depth = 1;
+        while depth > 0 {
+            self.buf.clear();
+            let event = self.reader.read_event_into(&mut self.buf)?;
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                Event::Eof => {
+                    return Err(quick_xml::Error::IllFormed(IllFormedError::MissingEndTag(
+                        "Ntry".to_owned(),
+                    ))
+                    .into())
+                }
+                _ => {}
+            }
+            writer.write_event(event)?;
+        }
+        self.frames.pop();
+
+        let ntry = String::from_utf8(writer.into_inner())
+            .map_err(|e| ParseCamt053Error::XmlError(e.into()));
+        let ntry_ref = ntry
+            .as_ref()
+            .ok()
+            .and_then(|xml| quick_xml::de::from_str::<NtryRef>(xml).ok())
+            .and_then(|n| n.ntry_ref);
+
+        Ok(Part::Entry {
+            ntry: ntry.and_then(|xml| deserialize_ntry(&xml).map(Box::new)),
+            ntry_ref,
+            location,
+        })
+    }
+
+    /// Reads events until at least one part is complete. Returns `false` at
+    /// the end of the file.
+    fn read_parts(&mut self) -> Result<bool, ParseCamt053Error> {
+        while self.parts.is_empty() {
+            self.buf.clear();
+            let event = self.reader.read_event_into(&mut self.buf)?;
+            match event {
+                Event::Start(start) => {
+                    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                    if self.frames.is_empty() {
+                        let offset = self.reader.buffer_position() - start.len() as u64 - 2;
+                        let version = root_version(&start);
+                        self.open(name);
+                        self.version =
+                            version.map_err(|e| e.located(self.location(offset), None))?;
+                        continue;
+                    }
+                    if name == "Ntry" && self.item_depth.is_some() {
+                        let start = start.into_owned();
+                        self.finish_header()?;
+                        let part = self.read_ntry(start)?;
+                        self.parts.push_back(part);
+                        continue;
+                    }
+
+                    if let Some(header) = self.header.as_mut() {
+                        header.writer.write_event(Event::Start(start.borrow()))?;
+                    } else if self.item_depth.is_none() && ITEMS.contains(&name.as_str()) {
+                        let offset = self.reader.buffer_position() - start.len() as u64 - 2;
+                        let mut writer = Writer::new(vec![]);
+                        writer.write_event(Event::Start(start.borrow()))?;
+                        self.item_depth = Some(self.frames.len());
+                        self.open(name.to_owned());
+                        self.header = Some(Header {
+                            writer,
+                            depth: self.frames.len() - 1,
+                            report: name == "Rpt",
+                            location: self.location(offset),
+                        });
+                        continue;
+                    }
+                    self.open(name);
+                }
+                Event::End(end) => {
+                    let depth = self.frames.len().saturating_sub(1);
+                    if let Some(header) = self.header.as_mut() {
+                        if depth > header.depth {
+                            header.writer.write_event(Event::End(end))?;
+                        }
+                    }
+                    if self.item_depth == Some(depth) {
+                        self.finish_header()?;
+                        self.item_depth = None;
+                    }
+                    self.frames.pop();
+                }
+                Event::Eof => {
+                    if let Some(frame) = self.frames.last() {
+                        return Err(quick_xml::Error::IllFormed(IllFormedError::MissingEndTag(
+                            frame.name.to_owned(),
+                        ))
+                        .into());
+                    }
+                    return Ok(false);
+                }
+                event => {
+                    if let Some(header) = self.header.as_mut() {
+                        header.writer.write_event(event)?;
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for DocumentReader<R> {
+    type Item = Result<Part, ParseCamt053Error>;
+
+    /// Stops after an error that leaves the rest of the file unreadable.
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(part) = self.parts.pop_front() {
+            return Some(Ok(part));
+        }
+        if self.done {
+            return None;
+        }
+
+        match self.read_parts() {
+            Ok(true) => self.parts.pop_front().map(Ok),
+            Ok(false) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                let location = self.location(self.reader.error_position());
+                Some(Err(e.located(location, None)))
+            }
+        }
+    }
+}
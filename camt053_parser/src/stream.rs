@@ -0,0 +1,134 @@
+use std::{collections::VecDeque, io::BufRead};
+
+use crate::{convert_ntry, reader::DocumentReader, reader::Part, ParseCamt053Error, Version};
+use common::Entry;
+
+/// Reads the entries of a camt file one `Ntry` at a time, so memory use
+/// doesn't grow with the size of the file, as the `parse_large_statement`
+/// bench checks. Yields the same entries as
+/// [`Camt053Parser::parse_file`](crate::Camt053Parser::parse_file).
+///
+/// An entry that can't be read or converted is yielded as an error and the
+/// stream continues with the next one. Errors that leave the rest of the
+/// file unreadable end the stream.
+pub struct EntryStream<R: BufRead> {
+    parts: DocumentReader<R>,
+    account: String,
+    report: bool,
+    version: Version,
+    entries: VecDeque<Entry>,
+}
+
+impl<R: BufRead> EntryStream<R> {
+    pub fn new(reader: R) -> Self {
+        EntryStream {
+            parts: DocumentReader::new(reader),
+            account: String::new(),
+            report: false,
+            version: Version::default(),
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for EntryStream<R> {
+    type Item = Result<Entry, ParseCamt053Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Some(Ok(entry));
+            }
+
+            match self.parts.next()? {
+                Ok(Part::Statement {
+                    stmt,
+                    report,
+                    version,
+                    ..
+                }) => {
+                    self.account = stmt.acct.id.account().to_owned();
+                    self.report = report;
+                    self.version = version;
+                }
+                Ok(Part::Entry {
+                    ntry,
+                    ntry_ref,
+                    location,
+                }) => match ntry
+                    .and_then(|n| convert_ntry(&self.account, *n, self.report, self.version))
+                {
+                    Ok(entries) => self.entries.extend(entries),
+                    Err(e) => return Some(Err(e.located(location, ntry_ref))),
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_entries_per_account() {
+        let xml = r#"<Document><BkToCstmrStmt>
+            <Stmt><Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+                <Ntry><Amt Ccy="EUR">1.50</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt>
+                    <NtryDtls><TxDtls><RltdPties><CdtrAcct><Id><IBAN>NL12RABO0123456789</IBAN></Id></CdtrAcct></RltdPties></TxDtls></NtryDtls>
+                </Ntry>
+            </Stmt>
+            <Stmt><Acct><Id><IBAN>NL86ASNB8830237086</IBAN></Id></Acct>
+                <Ntry><Amt Ccy="EUR">2</Amt><CdtDbtInd>CRDT</CdtDbtInd><BookgDt><Dt>2023-04-02</Dt></BookgDt></Ntry>
+            </Stmt>
+        </BkToCstmrStmt></Document>"#;
+
+        let entries = EntryStream::new(xml.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("entries to be read");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].account, "NL00BANK0000000000");
+        assert_eq!(
+            entries[0].counterparty_account,
+            Some("NL12RABO0123456789".to_string())
+        );
+        assert_eq!(entries[1].account, "NL86ASNB8830237086");
+    }
+
+    #[test]
+    fn fails_on_truncated_entry() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+            <Ntry><Amt Ccy="EUR">1.50</Amt>"#;
+
+        let mut stream = EntryStream::new(xml.as_bytes());
+
+        assert!(matches!(
+            stream.next(),
+            Some(Err(ParseCamt053Error::Located { source, .. }))
+                if matches!(*source, ParseCamt053Error::XmlError(_))
+        ));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn continues_after_invalid_entry() {
+        let xml = r#"<Document><BkToCstmrStmt><Stmt><Acct><Id><IBAN>NL00BANK0000000000</IBAN></Id></Acct>
+            <Ntry><NtryRef>1</NtryRef><Amt Ccy="EUR">1,50</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2023-04-01</Dt></BookgDt></Ntry>
+            <Ntry><NtryRef>2</NtryRef><Amt Ccy="EUR">2</Amt><CdtDbtInd>CRDT</CdtDbtInd><BookgDt><Dt>2023-04-02</Dt></BookgDt></Ntry>
+        </Stmt></BkToCstmrStmt></Document>"#;
+
+        let results = EntryStream::new(xml.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            &results[0],
+            Err(ParseCamt053Error::Located { ntry_ref: Some(r), .. }) if r == "1"
+        ));
+        assert_eq!(
+            results[1].as_ref().expect("entry to be read").reference,
+            Some("2".to_string())
+        );
+    }
+}
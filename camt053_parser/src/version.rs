@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+use crate::{ParseCamt053Error, NAMESPACES};
+
+/// The schema version of a camt message, read from the namespace of its
+/// `Document`. camt.052, camt.053 and camt.054 share their version numbers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Version {
+    /// 2009 release, e.g. `camt.053.001.02`.
+    #[default]
+    V02,
+    /// 2013 release, which adds `TxDtls/Amt` and renames `BIC` to `BICFI`.
+    V04,
+    /// 2019 release, which wraps the related party names in `Pty` and the
+    /// entry status in `Cd`.
+    V08,
+}
+
+impl Version {
+    /// The version of a camt namespace, e.g.
+    /// `urn:iso:std:iso:20022:tech:xsd:camt.053.001.08`.
+    pub fn from_namespace(namespace: &str) -> Result<Self, ParseCamt053Error> {
+        let version = NAMESPACES
+            .iter()
+            .find_map(|n| namespace.strip_prefix(n))
+            .and_then(|v| v.strip_prefix(".001."));
+
+        match version {
+            Some("02") => Ok(Version::V02),
+            Some("04") => Ok(Version::V04),
+            Some("08") => Ok(Version::V08),
+            _ => Err(ParseCamt053Error::UnsupportedNamespace(
+                namespace.to_owned(),
+            )),
+        }
+    }
+
+    /// The version of a document that doesn't declare a namespace.
+    pub fn from_optional_namespace(namespace: Option<&str>) -> Result<Self, ParseCamt053Error> {
+        namespace.map_or(Ok(Version::default()), Version::from_namespace)
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Version::V02 => write!(f, "001.02"),
+            Version::V04 => write!(f, "001.04"),
+            Version::V08 => write!(f, "001.08"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_supported_versions() {
+        for (namespace, version) in [
+            (
+                "urn:iso:std:iso:20022:tech:xsd:camt.053.001.02",
+                Version::V02,
+            ),
+            (
+                "urn:iso:std:iso:20022:tech:xsd:camt.052.001.04",
+                Version::V04,
+            ),
+            (
+                "urn:iso:std:iso:20022:tech:xsd:camt.054.001.08",
+                Version::V08,
+            ),
+        ] {
+            assert_eq!(
+                Version::from_namespace(namespace).expect("version to be supported"),
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_namespaces() {
+        for namespace in [
+            "urn:iso:std:iso:20022:tech:xsd:camt.053.001.03",
+            "urn:iso:std:iso:20022:tech:xsd:camt.053.001.10",
+            "urn:iso:std:iso:20022:tech:xsd:pain.001.001.03",
+            "",
+        ] {
+            assert!(matches!(
+                Version::from_namespace(namespace),
+                Err(ParseCamt053Error::UnsupportedNamespace(n)) if n == namespace
+            ));
+        }
+    }
+
+    #[test]
+    fn defaults_to_first_version_without_namespace() {
+        assert_eq!(
+            Version::from_optional_namespace(None).expect("version to be supported"),
+            Version::V02
+        );
+    }
+}
@@ -1,7 +1,12 @@
 use std::fs;
 
-use camt053_parser::Camt053Parser;
-use common::Entry;
+use camt053_parser::{Camt053Parser, EntryStream, ParseCamt053Error};
+use chrono::NaiveDate;
+use common::{
+    money::{Currency, ExchangeRate, Money},
+    parser::StatementParser,
+    ClearedState, Entry,
+};
 
 #[test]
 fn name() {
@@ -12,26 +17,335 @@ fn name() {
         .parse_file(&file_contents)
         .expect("xml file to be parsed");
 
+    let mut expected = Entry::new(
+        "NL00BANK0000000000".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 28).expect("date to be valid"),
+        Some("BEN NEDERLAND".to_string()),
+        Some("Klant 1.50884684 Factuur 908053695899".to_string()),
+        None,
+        Some(Money::parse("10", "EUR").expect("amount to be valid")),
+    );
+    expected.counterparty_account = Some("NL61COBA0733974775".to_string());
+    expected.counterparty_bic = Some("COBANL2X".to_string());
+    expected.bank_transaction_code = Some("9714".to_string());
+    expected.reference = Some("20230428-51415098".to_string());
+    assert_eq!(result.remove(0), expected);
+
+    let mut expected = Entry::new(
+        "NL00BANK0000000000".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 27).expect("date to be valid"),
+        Some("CCV*CAFETARIA SIM".to_string()),
+        Some(
+            "GRONINGEN27.04.2023 18U57 KV005 CT371516 MCC:5814 Contactloze betaling NLNEDERLAND"
+                .to_string(),
+        ),
+        None,
+        Some(Money::parse("19.3", "EUR").expect("amount to be valid")),
+    );
+    expected.bank_transaction_code = Some("7913".to_string());
+    expected.reference = Some("20230427-52801730".to_string());
+    assert_eq!(result.remove(0), expected);
+}
+
+#[test]
+fn counterparty_details() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/example.xml").expect("File to be read");
+
+    let result = camt053_parser
+        .parse_file(&file_contents)
+        .expect("xml file to be parsed");
+
+    let entry = result
+        .iter()
+        .find(|e| e.payee == Some("DELA Natura Levensverzekering".to_string()))
+        .expect("entry to be found");
+
+    assert_eq!(
+        entry.counterparty_account,
+        Some("NL15INGB0002286622".to_string())
+    );
+    assert_eq!(entry.counterparty_bic, Some("INGBNL2A".to_string()));
+    assert_eq!(entry.ultimate_debtor, Some("GPH Weggemans".to_string()));
+    assert_eq!(entry.ultimate_creditor, None);
+}
+
+#[test]
+fn statements_reconcile() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/example.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    assert_eq!(statements.len(), 2);
+    assert_eq!(
+        statements[0].opening_balance,
+        Some(Money::parse("413.42", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(
+        statements[0].closing_balance,
+        Some(Money::parse("1752.46", "EUR").expect("amount to be valid"))
+    );
+    for statement in statements {
+        assert_eq!(statement.reconcile(), Ok(()));
+    }
+}
+
+#[test]
+fn detects_format() {
+    let camt053_parser = Camt053Parser::create();
+    let xml = fs::read("resources/example.xml").expect("File to be read");
+    let csv = fs::read("../resources/example.csv").expect("File to be read");
+
+    assert!(camt053_parser.detect(&xml));
+    assert!(!camt053_parser.detect(&csv));
+}
+
+#[test]
+fn report_entries_are_uncleared() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt052.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].opening_balance, None);
+    assert_eq!(statements[0].closing_balance, None);
+
+    let mut expected = Entry::new(
+        "NL00BANK0000000000".to_string(),
+        NaiveDate::from_ymd_opt(2023, 5, 2).expect("date to be valid"),
+        Some("Bakkerij Bart".to_string()),
+        Some("Taart".to_string()),
+        None,
+        Some(Money::parse("25.00", "EUR").expect("amount to be valid")),
+    );
+    expected.reference = Some("20230502-51415201".to_string());
+    expected.cleared = ClearedState::Uncleared;
+    assert_eq!(statements[0].entries[0], expected);
+
+    let entry = &statements[0].entries[1];
     assert_eq!(
-        result.remove(0),
-        Entry::new(
-            "NL00BANK0000000000".to_string(),
-            "2023-04-28".to_string(),
-            Some("BEN NEDERLAND".to_string()),
-            Some("Klant 1.50884684 Factuur 908053695899".to_string()),
-            None,
-            Some("10".to_string())
-        )
+        entry.date,
+        NaiveDate::from_ymd_opt(2023, 5, 3).expect("date to be valid")
+    );
+    assert_eq!(entry.payee, Some("Koffiebar".to_string()));
+    assert_eq!(entry.cleared, ClearedState::Uncleared);
+}
+
+#[test]
+fn notification_entries() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt054.xml").expect("File to be read");
+
+    let mut result = camt053_parser
+        .parse_file(&file_contents)
+        .expect("xml file to be parsed");
+
+    let mut expected = Entry::new(
+        "NL00BANK0000000000".to_string(),
+        NaiveDate::from_ymd_opt(2023, 5, 2).expect("date to be valid"),
+        Some("J Doe".to_string()),
+        Some("Terugbetaling etentje".to_string()),
+        Some(Money::parse("150.00", "EUR").expect("amount to be valid")),
+        None,
     );
+    expected.counterparty_account = Some("NL12RABO0123456789".to_string());
+    expected.reference = Some("20230502-51415299".to_string());
+    assert_eq!(result.remove(0), expected);
+    assert!(result.is_empty());
+}
+
+#[test]
+fn detects_report_and_notification_format() {
+    let camt053_parser = Camt053Parser::create();
+
+    for path in ["resources/camt052.xml", "resources/camt054.xml"] {
+        let xml = fs::read(path).expect("File to be read");
+        assert!(camt053_parser.detect(&xml));
+    }
+}
+
+#[test]
+fn stream_matches_parse_file() {
+    let camt053_parser = Camt053Parser::create();
+
+    for path in [
+        "resources/example.xml",
+        "resources/camt052.xml",
+        "resources/camt054.xml",
+        "resources/camt053_v04.xml",
+        "resources/camt053_v08.xml",
+        "resources/camt053_othr.xml",
+        "resources/camt053_fx.xml",
+    ] {
+        let file_contents = fs::read_to_string(path).expect("File to be read");
+
+        let streamed = EntryStream::new(file_contents.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("xml file to be streamed");
+
+        assert_eq!(
+            streamed,
+            camt053_parser
+                .parse_file(&file_contents)
+                .expect("xml file to be parsed")
+        );
+    }
+}
+
+#[test]
+fn version_04_entries() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt053_v04.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].reconcile(), Ok(()));
+
+    let entries = &statements[0].entries;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].payee, Some("BEN NEDERLAND".to_string()));
+    assert_eq!(entries[0].counterparty_bic, Some("COBANL2X".to_string()));
+    assert_eq!(
+        entries[1].outflow,
+        Some(Money::parse("12.50", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(entries[1].payee, Some("Vattenfall".to_string()));
+    assert_eq!(
+        entries[2].outflow,
+        Some(Money::parse("17.50", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(entries[2].memo, Some("Voorschot mei".to_string()));
+}
+
+#[test]
+fn version_08_entries() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt053_v08.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].reconcile(), Ok(()));
+
+    let entries = &statements[0].entries;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].payee, Some("BEN NEDERLAND".to_string()));
+    assert_eq!(entries[0].cleared, ClearedState::Cleared);
+
+    let mut expected = Entry::new(
+        "NL00BANK0000000000".to_string(),
+        NaiveDate::from_ymd_opt(2023, 6, 25).expect("date to be valid"),
+        Some("Werkgever BV".to_string()),
+        Some("Salaris juni".to_string()),
+        Some(Money::parse("150.00", "EUR").expect("amount to be valid")),
+        None,
+    );
+    expected.counterparty_account = Some("NL15INGB0002286622".to_string());
+    expected.counterparty_bic = Some("INGBNL2A".to_string());
+    expected.ultimate_debtor = Some("Salarisadministratie".to_string());
+    expected.bank_transaction_code = Some("PMNT-RCDT-ESCT".to_string());
+    expected.reference = Some("20230625-51417002".to_string());
+    assert_eq!(entries[1], expected);
+
+    assert_eq!(entries[2].payee, Some("Koffiebar".to_string()));
+    assert_eq!(entries[2].cleared, ClearedState::Uncleared);
+}
+
+#[test]
+fn rejects_unsupported_version() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/example.xml")
+        .expect("File to be read")
+        .replace("camt.053.001.02", "camt.053.001.03");
+
+    let error = camt053_parser
+        .parse_statements(&file_contents)
+        .expect_err("version to be rejected");
+
+    assert!(matches!(
+        &error,
+        ParseCamt053Error::Located { location, source, .. }
+            if location.path == "Document"
+                && location.position.as_ref().map(|p| p.line) == Some(2)
+                && matches!(&**source, ParseCamt053Error::UnsupportedNamespace(n)
+                    if n == "urn:iso:std:iso:20022:tech:xsd:camt.053.001.03")
+    ));
+    assert!(error
+        .to_string()
+        .starts_with("Unsupported namespace 'urn:iso:std:iso:20022:tech:xsd:camt.053.001.03'"));
+}
+
+#[test]
+fn other_account_identifiers() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt053_othr.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    assert_eq!(statements[0].account, "5412750000001234");
+    assert_eq!(statements[0].reconcile(), Ok(()));
+
+    let entries = &statements[0].entries;
+    assert!(entries.iter().all(|e| e.account == "5412750000001234"));
+    assert_eq!(
+        entries[0].counterparty_account,
+        Some("NL61COBA0733974775".to_string())
+    );
+    assert_eq!(
+        entries[1].counterparty_account,
+        Some("0002286622".to_string())
+    );
+}
+
+#[test]
+fn foreign_currency_amounts() {
+    let camt053_parser = Camt053Parser::create();
+    let file_contents = fs::read_to_string("resources/camt053_fx.xml").expect("File to be read");
+
+    let statements = camt053_parser
+        .parse_statements(&file_contents)
+        .expect("xml file to be parsed");
+
+    let usd: Currency = "USD".parse().expect("currency to be valid");
+    let card_payment = &statements[0].entries[0];
+    assert_eq!(
+        card_payment.outflow,
+        Some(Money::parse("11.05", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(
+        card_payment.original_amount,
+        Some(Money::parse("12.00", "USD").expect("amount to be valid"))
+    );
+    assert_eq!(
+        card_payment.exchange_rate,
+        Some(ExchangeRate {
+            source: usd,
+            target: Currency::EUR,
+            rate: "0.9208".parse().expect("rate to be valid"),
+        })
+    );
+
+    let usd_entry = &statements[1].entries[0];
     assert_eq!(
-        result.remove(0),
-        Entry::new(
-            "NL00BANK0000000000".to_string(),
-            "2023-04-27".to_string(),
-            Some("CCV*CAFETARIA SIM".to_string()),
-            Some("GRONINGEN27.04.2023 18U57 KV005 CT371516 MCC:5814 Contactloze betaling NLNEDERLAND".to_string()),
-            None,
-            Some("19.3".to_string())
-        )
+        usd_entry.inflow,
+        Some(Money::parse("25.00", "USD").expect("amount to be valid"))
     );
+    assert_eq!(usd_entry.original_amount, None);
+    assert_eq!(usd_entry.exchange_rate, None);
+    for statement in statements {
+        assert_eq!(statement.reconcile(), Ok(()));
+    }
 }
@@ -1,21 +1,128 @@
-#[derive(Debug, PartialEq)]
+pub mod money;
+pub mod parser;
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::money::{Currency, ExchangeRate, Money};
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl Display for FlagColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlagColor::Red => write!(f, "Red"),
+            FlagColor::Orange => write!(f, "Orange"),
+            FlagColor::Yellow => write!(f, "Yellow"),
+            FlagColor::Green => write!(f, "Green"),
+            FlagColor::Blue => write!(f, "Blue"),
+            FlagColor::Purple => write!(f, "Purple"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ClearedState {
+    Cleared,
+    Uncleared,
+}
+
+impl Display for ClearedState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClearedState::Cleared => write!(f, "Cleared"),
+            ClearedState::Uncleared => write!(f, "Uncleared"),
+        }
+    }
+}
+
+/// Position of an entry within a batch booking that was expanded into one
+/// entry per transaction, e.g. split 2 of 3.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Split {
+    pub index: usize,
+    pub count: usize,
+}
+
+/// An account identifier in a comparable form, so IBANs and other
+/// identifiers match however they are spaced or cased.
+pub fn normalize_account(account: &str) -> String {
+    account
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Splits a description such as `ALBERT HEIJN 1631 >GRONINGEN` into the
+/// payee before the `>` and the memo after it. Memos without `>` are kept
+/// with the given payee.
+pub fn split_payee_memo(
+    payee: Option<String>,
+    memo: Option<String>,
+) -> (Option<String>, Option<String>) {
+    if let Some(txt) = memo.as_ref() {
+        let memo_split = txt.split('>').collect::<Vec<_>>();
+        if memo_split.len() > 1 {
+            return (
+                Some(memo_split[0].trim().to_owned()),
+                Some(memo_split[1].trim().to_owned()),
+            );
+        }
+    }
+
+    (payee, memo)
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Entry {
     pub account: String,
-    pub date: String,
+    pub date: NaiveDate,
     pub payee: Option<String>,
     pub memo: Option<String>,
-    pub inflow: Option<String>,
-    pub outflow: Option<String>,
+    pub inflow: Option<Money>,
+    pub outflow: Option<Money>,
+    pub category: Option<String>,
+    pub flag: Option<FlagColor>,
+    pub counterparty_account: Option<String>,
+    pub counterparty_bic: Option<String>,
+    pub ultimate_debtor: Option<String>,
+    pub ultimate_creditor: Option<String>,
+    pub bank_transaction_code: Option<String>,
+    pub reference: Option<String>,
+    pub import_id: Option<String>,
+    pub reversal: bool,
+    pub cleared: ClearedState,
+    pub split: Option<Split>,
+    /// The amount before conversion, or the amount instructed in another
+    /// currency, such as the price of a foreign card payment.
+    pub original_amount: Option<Money>,
+    /// The rate the bank applied to a payment in another currency.
+    pub exchange_rate: Option<ExchangeRate>,
+    /// The alias of our own account on the other side of a transfer.
+    pub transfer: Option<String>,
 }
 
 impl Entry {
     pub fn new(
         account: String,
-        date: String,
+        date: NaiveDate,
         payee: Option<String>,
         memo: Option<String>,
-        inflow: Option<String>,
-        outflow: Option<String>,
+        inflow: Option<Money>,
+        outflow: Option<Money>,
     ) -> Self {
         Entry {
             account,
@@ -24,11 +131,213 @@ impl Entry {
             memo,
             inflow,
             outflow,
+            category: None,
+            flag: None,
+            counterparty_account: None,
+            counterparty_bic: None,
+            ultimate_debtor: None,
+            ultimate_creditor: None,
+            bank_transaction_code: None,
+            reference: None,
+            import_id: None,
+            reversal: false,
+            cleared: ClearedState::Cleared,
+            split: None,
+            original_amount: None,
+            exchange_rate: None,
+            transfer: None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Error, Debug, PartialEq)]
+pub enum ReconcileError {
+    #[error("Statement for {account} mixes {expected} and {found} amounts")]
+    CurrencyMismatch {
+        account: String,
+        expected: Currency,
+        found: Currency,
+    },
+    #[error(
+        "Statement for {account} does not reconcile: opening balance plus entries is {expected}, closing balance is {closing}"
+    )]
+    Mismatch {
+        account: String,
+        expected: Decimal,
+        closing: Decimal,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Statement {
+    pub account: String,
+    pub opening_balance: Option<Money>,
+    pub closing_balance: Option<Money>,
     pub entries: Vec<Entry>,
 }
+
+impl Statement {
+    /// Checks that the opening balance plus booked credits minus booked debits
+    /// equals the closing balance. Statements without both balances are not
+    /// checked.
+    pub fn reconcile(&self) -> Result<(), ReconcileError> {
+        let (Some(opening), Some(closing)) = (self.opening_balance, self.closing_balance) else {
+            return Ok(());
+        };
+
+        let mut expected = opening.amount;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.cleared == ClearedState::Cleared)
+        {
+            let (money, sign) = match (entry.inflow, entry.outflow) {
+                (Some(inflow), _) => (inflow, Decimal::ONE),
+                (None, Some(outflow)) => (outflow, Decimal::NEGATIVE_ONE),
+                (None, None) => continue,
+            };
+
+            if money.currency != opening.currency {
+                return Err(ReconcileError::CurrencyMismatch {
+                    account: self.account.to_owned(),
+                    expected: opening.currency,
+                    found: money.currency,
+                });
+            }
+
+            expected += money.amount * sign;
+        }
+
+        if closing.currency != opening.currency {
+            return Err(ReconcileError::CurrencyMismatch {
+                account: self.account.to_owned(),
+                expected: opening.currency,
+                found: closing.currency,
+            });
+        }
+
+        if expected != closing.amount {
+            return Err(ReconcileError::Mismatch {
+                account: self.account.to_owned(),
+                expected,
+                closing: closing.amount,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: &str) -> Money {
+        Money::parse(amount, "EUR").expect("amount to be valid")
+    }
+
+    fn entry(inflow: Option<&str>, outflow: Option<&str>) -> Entry {
+        Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 28).expect("date to be valid"),
+            None,
+            None,
+            inflow.map(money),
+            outflow.map(money),
+        )
+    }
+
+    fn statement(opening: Option<&str>, closing: Option<&str>, entries: Vec<Entry>) -> Statement {
+        Statement {
+            account: "NL00BANK0000000000".to_string(),
+            opening_balance: opening.map(money),
+            closing_balance: closing.map(money),
+            entries,
+        }
+    }
+
+    #[test]
+    fn splits_payee_from_memo() {
+        let some = |s: &str| Some(s.to_string());
+
+        assert_eq!(
+            split_payee_memo(some("Albert Heijn"), some("ALBERT HEIJN 1631 >GRONINGEN")),
+            (some("ALBERT HEIJN 1631"), some("GRONINGEN"))
+        );
+        assert_eq!(
+            split_payee_memo(some("J Doe"), some("Invoice 123")),
+            (some("J Doe"), some("Invoice 123"))
+        );
+        assert_eq!(split_payee_memo(None, None), (None, None));
+    }
+
+    #[test]
+    fn normalizes_accounts() {
+        assert_eq!(
+            normalize_account(" nl00 bank 0000 0000 00\t"),
+            "NL00BANK0000000000"
+        );
+        assert_eq!(normalize_account("5412 7500 0000 1234"), "5412750000001234");
+    }
+
+    #[test]
+    fn statement_reconciles() {
+        let statement = statement(
+            Some("413.42"),
+            Some("404.22"),
+            vec![entry(None, Some("10")), entry(Some("0.8"), None)],
+        );
+
+        assert_eq!(statement.reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn statement_without_balances_is_not_checked() {
+        let statement = statement(None, Some("1"), vec![entry(None, Some("10"))]);
+
+        assert_eq!(statement.reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn pending_entries_are_not_reconciled() {
+        let mut pending = entry(Some("5"), None);
+        pending.cleared = ClearedState::Uncleared;
+        let statement = statement(
+            Some("413.42"),
+            Some("403.42"),
+            vec![entry(None, Some("10")), pending],
+        );
+
+        assert_eq!(statement.reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn truncated_statement_does_not_reconcile() {
+        let statement = statement(
+            Some("413.42"),
+            Some("404.22"),
+            vec![entry(None, Some("10"))],
+        );
+
+        assert_eq!(
+            statement.reconcile(),
+            Err(ReconcileError::Mismatch {
+                account: "NL00BANK0000000000".to_string(),
+                expected: Decimal::new(40342, 2),
+                closing: Decimal::new(40422, 2),
+            })
+        );
+    }
+
+    #[test]
+    fn mixed_currencies_do_not_reconcile() {
+        let mut foreign = entry(None, Some("10"));
+        foreign.outflow = Some(Money::parse("10", "USD").expect("amount to be valid"));
+        let statement = statement(Some("10"), Some("0"), vec![foreign]);
+
+        assert!(matches!(
+            statement.reconcile(),
+            Err(ReconcileError::CurrencyMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use std::{fmt::Display, str::FromStr};
+
+use rust_decimal::{prelude::ToPrimitive, Decimal, RoundingStrategy};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MoneyError {
+    #[error("Invalid amount '{0}'")]
+    InvalidAmount(String),
+    #[error("Invalid currency code '{0}'")]
+    InvalidCurrency(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const EUR: Currency = Currency(*b"EUR");
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [a, b, c] if s.bytes().all(|b| b.is_ascii_uppercase()) => Ok(Currency([*a, *b, *c])),
+            _ => Err(MoneyError::InvalidCurrency(s.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = MoneyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Only ever constructed from three ASCII uppercase letters.
+        write!(f, "{}", std::str::from_utf8(&self.0).unwrap_or("???"))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+
+    /// Parses a plain decimal amount like `19.3` or `-1200.00`. Thousands
+    /// separators, exponents and decimal commas are rejected.
+    pub fn parse(amount: &str, currency: &str) -> Result<Self, MoneyError> {
+        let invalid = || MoneyError::InvalidAmount(amount.to_owned());
+
+        let digits = amount.strip_prefix('-').unwrap_or(amount);
+        let (units, fraction) = digits.split_once('.').unwrap_or((digits, "0"));
+        if units.is_empty()
+            || fraction.is_empty()
+            || !units.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let amount = Decimal::from_str(amount).map_err(|_| invalid())?;
+
+        Ok(Money::new(amount, currency.parse()?))
+    }
+
+    /// The amount in YNAB milliunits, rounded to three decimals.
+    pub fn milliunits(&self) -> Option<i64> {
+        (self.amount * Decimal::ONE_THOUSAND).round().to_i64()
+    }
+}
+
+impl Display for Money {
+    /// Formats the amount with at least two decimals, without currency.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut amount = self.amount;
+        if amount.scale() < 2 {
+            amount.rescale(2);
+        }
+
+        write!(f, "{}", amount)
+    }
+}
+
+/// An exchange rate: one unit of `source` is worth `rate` units of
+/// `target`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ExchangeRate {
+    pub source: Currency,
+    pub target: Currency,
+    pub rate: Decimal,
+}
+
+impl ExchangeRate {
+    /// Converts money in either currency of the rate into the other,
+    /// rounded to cents. Returns `None` for other currencies.
+    pub fn convert(&self, money: Money, currency: Currency) -> Option<Money> {
+        let amount = if (money.currency, currency) == (self.source, self.target) {
+            money.amount * self.rate
+        } else if (money.currency, currency) == (self.target, self.source) && !self.rate.is_zero() {
+            money.amount / self.rate
+        } else {
+            return None;
+        };
+
+        Some(Money::new(
+            amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+            currency,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_amounts() {
+        let money = Money::parse("19.3", "EUR").expect("amount to be parsed");
+
+        assert_eq!(money.amount, Decimal::new(193, 1));
+        assert_eq!(money.currency, Currency::EUR);
+        assert_eq!(money.to_string(), "19.30");
+        assert_eq!(money.milliunits(), Some(19300));
+        assert_eq!(
+            Money::parse("-1200", "USD").map(|m| m.to_string()),
+            Ok("-1200.00".to_string())
+        );
+        assert_eq!(
+            Money::parse("0.125", "BHD").map(|m| m.to_string()),
+            Ok("0.125".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for amount in ["", "19,30", "1_000", "1e3", "1.", ".5", "abc", "--1"] {
+            assert_eq!(
+                Money::parse(amount, "EUR"),
+                Err(MoneyError::InvalidAmount(amount.to_string())),
+                "{}",
+                amount
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_currencies() {
+        for currency in ["", "eur", "EURO", "E1R"] {
+            assert_eq!(
+                Money::parse("1", currency),
+                Err(MoneyError::InvalidCurrency(currency.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn converts_with_exchange_rate() {
+        let usd: Currency = "USD".parse().expect("currency to be valid");
+        let rate = ExchangeRate {
+            source: usd,
+            target: Currency::EUR,
+            rate: Decimal::new(9208, 4),
+        };
+
+        assert_eq!(
+            rate.convert(
+                Money::parse("12.00", "USD").expect("amount to be valid"),
+                Currency::EUR
+            ),
+            Some(Money::parse("11.05", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(
+            rate.convert(
+                Money::parse("11.05", "EUR").expect("amount to be valid"),
+                usd
+            ),
+            Some(Money::parse("12.00", "USD").expect("amount to be valid"))
+        );
+        assert_eq!(
+            rate.convert(
+                Money::parse("1", "GBP").expect("amount to be valid"),
+                Currency::EUR
+            ),
+            None
+        );
+    }
+}
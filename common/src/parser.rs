@@ -0,0 +1,79 @@
+use std::{error::Error, io::BufRead};
+
+use thiserror::Error;
+
+use crate::{Entry, Statement};
+
+/// Number of leading bytes inspected when detecting the format.
+pub const SNIFF_LENGTH: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum ParseStatementError {
+    #[error("File is not valid UTF-8")]
+    EncodingError(#[from] std::str::Utf8Error),
+    #[error("Error reading file: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error(transparent)]
+    ParserError(Box<dyn Error + Send + Sync>),
+}
+
+impl ParseStatementError {
+    pub fn parser(error: impl Error + Send + Sync + 'static) -> Self {
+        ParseStatementError::ParserError(Box::new(error))
+    }
+}
+
+/// A bank statement format the app can import.
+pub trait StatementParser {
+    /// Short name used to select the parser explicitly, e.g. `camt053`.
+    fn name(&self) -> &str;
+
+    /// Whether the file contents look like this format. Only sniffs the
+    /// contents, so a detected file may still fail to parse.
+    fn detect(&self, contents: &[u8]) -> bool;
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError>;
+
+    /// Like `parse_statements`, but skips broken records and returns why
+    /// they were skipped, per statement. Formats that can't skip records
+    /// parse strictly.
+    fn parse_statements_lenient(
+        &self,
+        contents: &[u8],
+    ) -> Result<Vec<(Statement, Vec<String>)>, ParseStatementError> {
+        Ok(self
+            .parse_statements(contents)?
+            .into_iter()
+            .map(|s| (s, vec![]))
+            .collect())
+    }
+
+    /// Parses the statements read from `reader`, leniently or strictly.
+    /// Formats that can read a file in parts override this, so large files
+    /// don't have to be read into memory. Others read the whole file first.
+    fn read_statements(
+        &self,
+        reader: &mut dyn BufRead,
+        lenient: bool,
+    ) -> Result<Vec<(Statement, Vec<String>)>, ParseStatementError> {
+        let mut contents = vec![];
+        reader.read_to_end(&mut contents)?;
+
+        match lenient {
+            true => self.parse_statements_lenient(&contents),
+            false => Ok(self
+                .parse_statements(&contents)?
+                .into_iter()
+                .map(|s| (s, vec![]))
+                .collect()),
+        }
+    }
+
+    fn parse(&self, contents: &[u8]) -> Result<Vec<Entry>, ParseStatementError> {
+        Ok(self
+            .parse_statements(contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+}
@@ -0,0 +1,66 @@
+use std::{borrow::Cow, str::Utf8Error};
+
+use crate::model::Encoding;
+
+/// The characters of Windows-1252 bytes `0x80` to `0x9F`. Unassigned bytes
+/// map to the C1 control character with the same value, like ISO-8859-1.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+impl Encoding {
+    /// Decodes the contents, skipping a UTF-8 byte order mark.
+    pub fn decode<'a>(&self, contents: &'a [u8]) -> Result<Cow<'a, str>, Utf8Error> {
+        match self {
+            Encoding::Utf8 => {
+                let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+                Ok(Cow::Borrowed(std::str::from_utf8(contents)?))
+            }
+            Encoding::Latin1 => Ok(Cow::Owned(contents.iter().map(|b| *b as char).collect())),
+            Encoding::Windows1252 => Ok(Cow::Owned(
+                contents
+                    .iter()
+                    .map(|b| match b {
+                        0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                        _ => *b as char,
+                    })
+                    .collect(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_single_byte_encodings() {
+        let contents = b"Caf\xE9 \x80 5";
+
+        assert_eq!(
+            Encoding::Latin1
+                .decode(contents)
+                .expect("contents to be decoded"),
+            "Café \u{80} 5"
+        );
+        assert_eq!(
+            Encoding::Windows1252
+                .decode(contents)
+                .expect("contents to be decoded"),
+            "Café € 5"
+        );
+        assert!(Encoding::Utf8.decode(contents).is_err());
+    }
+
+    #[test]
+    fn skips_byte_order_mark() {
+        assert_eq!(
+            Encoding::Utf8
+                .decode(b"\xEF\xBB\xBFDatum")
+                .expect("contents to be decoded"),
+            "Datum"
+        );
+    }
+}
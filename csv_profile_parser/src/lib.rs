@@ -0,0 +1,550 @@
+mod encoding;
+pub mod model;
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::model::{Column, CsvProfile, CsvTable};
+use common::{
+    money::{Money, MoneyError},
+    parser::{ParseStatementError, StatementParser, SNIFF_LENGTH},
+    Entry, Statement,
+};
+
+#[derive(Error, Debug)]
+pub enum ParseCsvProfileError {
+    #[error("Error parsing csv: {0}")]
+    ParseError(#[from] csv::Error),
+    #[error("Invalid amount '{value}' in column {column}: {source}")]
+    AmountError {
+        column: String,
+        value: String,
+        source: MoneyError,
+    },
+    #[error("Invalid date '{0}'")]
+    DateError(String),
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+    #[error("Row {row}: {source}")]
+    InvalidRow {
+        row: usize,
+        source: Box<ParseCsvProfileError>,
+    },
+}
+
+fn value<'a>(
+    table: &CsvTable,
+    row: &'a [String],
+    column: &Column,
+) -> Result<&'a str, ParseCsvProfileError> {
+    let index = match column {
+        Column::Index(index) => Some(*index),
+        Column::Name(name) => table
+            .headers
+            .iter()
+            .position(|h| h.trim().eq(name))
+            .or(name.parse().ok()),
+    };
+
+    index
+        .and_then(|index| row.get(index))
+        .map(|v| v.trim())
+        .ok_or_else(|| ParseCsvProfileError::UnknownColumn(column.to_string()))
+}
+
+/// Replaces every `{column}` in the template by the value of that column.
+fn render(
+    template: &str,
+    table: &CsvTable,
+    row: &[String],
+) -> Result<String, ParseCsvProfileError> {
+    let mut result = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            ParseCsvProfileError::InvalidProfile(format!("unclosed '{{' in '{}'", template))
+        })? + start;
+        result.push_str(&rest[..start]);
+        result.push_str(value(
+            table,
+            row,
+            &Column::Name(rest[start + 1..end].to_owned()),
+        )?);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn render_optional(
+    template: Option<&String>,
+    table: &CsvTable,
+    row: &[String],
+) -> Result<Option<String>, ParseCsvProfileError> {
+    let Some(template) = template else {
+        return Ok(None);
+    };
+    let text = render(template, table, row)?
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(Some(text).filter(|t| !t.is_empty()))
+}
+
+/// Parses an amount with optional thousands separators, e.g. `-1.234,56`
+/// with a decimal comma or `+1,234.56` without. A thousands separator must be
+/// followed by exactly three digits, so `12,50` without a decimal comma is
+/// rejected instead of read as `1250`.
+fn parse_amount(amount: &str, currency: &str, decimal_comma: bool) -> Result<Money, MoneyError> {
+    let (thousands, decimal) = if decimal_comma {
+        ('.', ',')
+    } else {
+        (',', '.')
+    };
+    let trimmed = amount.trim().trim_start_matches('+');
+    let units = trimmed.split(decimal).next().unwrap_or_default();
+    let mut groups = units.trim_start_matches('-').split(thousands);
+    let first = groups.next().unwrap_or_default();
+    let grouped = groups
+        .map(|g| g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit()))
+        .collect::<Vec<_>>();
+    if !grouped.is_empty() && (first.is_empty() || first.len() > 3 || grouped.contains(&false)) {
+        return Err(MoneyError::InvalidAmount(amount.to_owned()));
+    }
+
+    Money::parse(
+        &trimmed.replace(thousands, "").replace(decimal, "."),
+        currency,
+    )
+}
+
+fn convert_row(
+    profile: &CsvProfile,
+    table: &CsvTable,
+    row: &[String],
+) -> Result<Entry, ParseCsvProfileError> {
+    let date = value(table, row, &profile.date)?;
+    let date = NaiveDate::parse_from_str(date, &profile.date_format)
+        .map_err(|_| ParseCsvProfileError::DateError(date.to_owned()))?;
+    let currency = render(&profile.currency, table, row)?;
+    let parse = |column: &Column| {
+        let amount = value(table, row, column)?;
+        match amount.is_empty() {
+            true => Money::parse("0", &currency),
+            false => parse_amount(amount, &currency, profile.decimal_comma),
+        }
+        .map_err(|source| ParseCsvProfileError::AmountError {
+            column: column.to_string(),
+            value: amount.to_owned(),
+            source,
+        })
+    };
+
+    let mut amount = match (&profile.amount, &profile.inflow, &profile.outflow) {
+        (Some(amount), _, _) => {
+            let mut money = parse(amount)?;
+            if let Some(direction) = profile.direction.as_ref() {
+                money.amount = money.amount.abs();
+                if value(table, row, &direction.column)?.eq_ignore_ascii_case(&direction.outflow) {
+                    money.amount = -money.amount;
+                }
+            }
+            money
+        }
+        (None, Some(inflow), Some(outflow)) => {
+            let mut money = parse(inflow)?;
+            money.amount = money.amount.abs() - parse(outflow)?.amount.abs();
+            money
+        }
+        _ => {
+            return Err(ParseCsvProfileError::InvalidProfile(
+                "set either amount or inflow and outflow".to_owned(),
+            ))
+        }
+    };
+
+    let mut entry = Entry::new(
+        render(&profile.account, table, row)?,
+        date,
+        render_optional(profile.payee.as_ref(), table, row)?,
+        render_optional(profile.memo.as_ref(), table, row)?,
+        None,
+        None,
+    );
+    entry.counterparty_account =
+        render_optional(profile.counterparty_account.as_ref(), table, row)?;
+    entry.reference = render_optional(profile.reference.as_ref(), table, row)?;
+
+    if amount.amount.is_sign_negative() {
+        amount.amount = -amount.amount;
+        entry.outflow = Some(amount);
+    } else {
+        entry.inflow = Some(amount);
+    }
+
+    Ok(entry)
+}
+
+/// Groups the rows into one statement per account. Exports don't have
+/// statement balances, so statements are not reconciled. Errors are numbered
+/// by their row in the file, counting skipped rows and the header.
+fn convert_table(
+    profile: &CsvProfile,
+    table: CsvTable,
+) -> Result<Vec<Statement>, ParseCsvProfileError> {
+    let mut statements: Vec<Statement> = vec![];
+
+    let first_row = profile.skip_rows + usize::from(profile.header) + 1;
+    for (index, row) in table.rows.iter().enumerate() {
+        let entry =
+            convert_row(profile, &table, row).map_err(|e| ParseCsvProfileError::InvalidRow {
+                row: first_row + index,
+                source: Box::new(e),
+            })?;
+
+        match statements.iter_mut().find(|s| s.account.eq(&entry.account)) {
+            Some(statement) => statement.entries.push(entry),
+            None => statements.push(Statement {
+                account: entry.account.to_owned(),
+                opening_balance: None,
+                closing_balance: None,
+                entries: vec![entry],
+            }),
+        }
+    }
+
+    Ok(statements)
+}
+
+fn read_table(profile: &CsvProfile, csv_contents: &str) -> Result<CsvTable, ParseCsvProfileError> {
+    let delimiter = u8::try_from(profile.delimiter).map_err(|_| {
+        ParseCsvProfileError::InvalidProfile("delimiter must be an ASCII character".to_owned())
+    })?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(csv_contents.as_bytes());
+
+    let mut records = reader
+        .records()
+        .skip(profile.skip_rows)
+        .map(|r| r.map(|r| r.iter().map(|v| v.to_owned()).collect::<Vec<_>>()));
+
+    let headers = match profile.header {
+        true => records.next().transpose()?.unwrap_or_default(),
+        false => vec![],
+    };
+
+    Ok(CsvTable {
+        headers,
+        rows: records.collect::<Result<Vec<_>, _>>()?,
+    })
+}
+
+/// Parses CSV exports described by a [`CsvProfile`] from the config.
+pub struct CsvProfileParser {
+    name: String,
+    profile: CsvProfile,
+    csv_reader: Box<dyn CsvReader>,
+}
+
+impl CsvProfileParser {
+    pub fn create_nullable(name: &str, profile: CsvProfile, table: CsvTable) -> Self {
+        CsvProfileParser {
+            name: name.to_owned(),
+            profile,
+            csv_reader: Box::new(StubbedCsvReader { table }),
+        }
+    }
+
+    pub fn create(name: &str, profile: CsvProfile) -> Self {
+        CsvProfileParser {
+            name: name.to_owned(),
+            csv_reader: Box::new(RealCsvReader {
+                profile: profile.clone(),
+            }),
+            profile,
+        }
+    }
+
+    pub fn parse_file(&self, csv_contents: &str) -> Result<Vec<Entry>, ParseCsvProfileError> {
+        Ok(self
+            .parse_statements(csv_contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+
+    pub fn parse_statements(
+        &self,
+        csv_contents: &str,
+    ) -> Result<Vec<Statement>, ParseCsvProfileError> {
+        let table = self.csv_reader.read_from_str(csv_contents)?;
+
+        convert_table(&self.profile, table)
+    }
+}
+
+impl StatementParser for CsvProfileParser {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Detected when the columns of the profile exist and the first row can
+    /// be converted.
+    fn detect(&self, contents: &[u8]) -> bool {
+        let head = &contents[..contents.len().min(SNIFF_LENGTH)];
+        let head = match self.profile.encoding.decode(head) {
+            Ok(head) => head,
+            Err(e) => String::from_utf8_lossy(&head[..e.valid_up_to()]),
+        };
+
+        read_table(&self.profile, &head).is_ok_and(|table| {
+            table
+                .rows
+                .first()
+                .is_some_and(|row| convert_row(&self.profile, &table, row).is_ok())
+        })
+    }
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError> {
+        CsvProfileParser::parse_statements(self, &self.profile.encoding.decode(contents)?)
+            .map_err(ParseStatementError::parser)
+    }
+}
+
+trait CsvReader {
+    fn read_from_str(&self, csv_contents: &str) -> Result<CsvTable, ParseCsvProfileError>;
+}
+
+struct RealCsvReader {
+    profile: CsvProfile,
+}
+
+impl CsvReader for RealCsvReader {
+    fn read_from_str(&self, csv_contents: &str) -> Result<CsvTable, ParseCsvProfileError> {
+        read_table(&self.profile, csv_contents)
+    }
+}
+
+struct StubbedCsvReader {
+    table: CsvTable,
+}
+
+impl CsvReader for StubbedCsvReader {
+    fn read_from_str(&self, _csv_contents: &str) -> Result<CsvTable, ParseCsvProfileError> {
+        Ok(self.table.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DirectionColumn;
+
+    fn table(headers: &[&str], rows: &[&[&str]]) -> CsvTable {
+        CsvTable {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|r| r.iter().map(|v| v.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_empty_csv_profile_parser() {
+        let parser = CsvProfileParser::create_nullable(
+            "bank",
+            CsvProfile::new("{0}", Column::Index(1), "%Y-%m-%d", Column::Index(2)),
+            CsvTable::default(),
+        );
+
+        assert_eq!(
+            parser.parse_file("mocked").expect("File to be parsed"),
+            vec![]
+        )
+    }
+
+    #[test]
+    fn test_signed_amount_with_templates() {
+        let mut profile = CsvProfile::new(
+            "{Account}",
+            Column::Name("Date".to_string()),
+            "%d-%m-%Y",
+            Column::Name("Amount".to_string()),
+        );
+        profile.decimal_comma = true;
+        profile.payee = Some("{Name}".to_string());
+        profile.memo = Some("{Description}  {Reference}".to_string());
+        profile.counterparty_account = Some("{Counterparty}".to_string());
+        let parser = CsvProfileParser::create_nullable(
+            "bank",
+            profile,
+            table(
+                &[
+                    "Date",
+                    "Account",
+                    "Counterparty",
+                    "Name",
+                    "Amount",
+                    "Description",
+                    "Reference",
+                ],
+                &[
+                    &[
+                        "23-04-2023",
+                        "NL00BANK0000000000",
+                        "NL12RABO0123456789",
+                        "J Doe",
+                        "-1.234,50",
+                        "Rent",
+                        "",
+                    ],
+                    &["24-04-2023", "NL00BANK0000000001", "", "", "+7,5", "", ""],
+                ],
+            ),
+        );
+
+        let statements = parser
+            .parse_statements("mocked")
+            .expect("File to be parsed");
+
+        let mut expected = Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 23).expect("date to be valid"),
+            Some("J Doe".to_string()),
+            Some("Rent".to_string()),
+            None,
+            Some(Money::parse("1234.50", "EUR").expect("amount to be valid")),
+        );
+        expected.counterparty_account = Some("NL12RABO0123456789".to_string());
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].entries, vec![expected]);
+        assert_eq!(
+            statements[1].entries[0].inflow,
+            Some(Money::parse("7.5", "EUR").expect("amount to be valid"))
+        );
+        assert_eq!(statements[1].entries[0].payee, None);
+    }
+
+    #[test]
+    fn test_direction_and_separate_amount_columns() {
+        let mut direction = CsvProfile::new(
+            "NL00BANK0000000000",
+            Column::Index(0),
+            "%Y%m%d",
+            Column::Index(1),
+        );
+        direction.direction = Some(DirectionColumn {
+            column: Column::Index(2),
+            outflow: "Af".to_string(),
+        });
+        let mut separate = direction.clone();
+        separate.amount = None;
+        separate.direction = None;
+        separate.inflow = Some(Column::Index(3));
+        separate.outflow = Some(Column::Index(4));
+        let rows = table(
+            &[],
+            &[
+                &["20230423", "12.50", "Af", "", "12.50"],
+                &["20230424", "3.00", "Bij", "3.00", ""],
+            ],
+        );
+
+        for profile in [direction, separate] {
+            let parser = CsvProfileParser::create_nullable("bank", profile, rows.clone());
+
+            let entries = parser.parse_file("mocked").expect("File to be parsed");
+
+            assert_eq!(
+                entries[0].outflow,
+                Some(Money::parse("12.50", "EUR").expect("amount to be valid"))
+            );
+            assert_eq!(
+                entries[1].inflow,
+                Some(Money::parse("3.00", "EUR").expect("amount to be valid"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let mut profile = CsvProfile::new(
+            "NL00BANK0000000000",
+            Column::Index(0),
+            "%Y%m%d",
+            Column::Index(1),
+        );
+        profile.memo = Some("{Description}".to_string());
+        let parser = CsvProfileParser::create_nullable(
+            "bank",
+            profile,
+            table(&["Date", "Amount"], &[&["20230423", "1.00"]]),
+        );
+
+        let result = parser.parse_file("mocked");
+
+        assert!(matches!(
+            result,
+            Err(ParseCsvProfileError::InvalidRow { row: 2, source })
+                if matches!(&*source, ParseCsvProfileError::UnknownColumn(column) if column == "Description")
+        ));
+    }
+
+    #[test]
+    fn test_thousands_separators() {
+        let parse = |amount: &str, decimal_comma: bool| {
+            parse_amount(amount, "EUR", decimal_comma)
+                .ok()
+                .map(|m| m.amount.to_string())
+        };
+
+        assert_eq!(parse("-1.234,56", true), Some("-1234.56".to_string()));
+        assert_eq!(parse("+1,234,567.5", false), Some("1234567.5".to_string()));
+        assert_eq!(parse("12,50", true), Some("12.50".to_string()));
+        assert_eq!(parse("12.50", false), Some("12.50".to_string()));
+    }
+
+    #[test]
+    fn test_rejects_mismatched_decimal_separator() {
+        assert!(parse_amount("12,50", "EUR", false).is_err());
+        assert!(parse_amount("12.50", "EUR", true).is_err());
+        assert!(parse_amount("1,2345.00", "EUR", false).is_err());
+        assert!(parse_amount(",500", "EUR", false).is_err());
+    }
+
+    #[test]
+    fn test_invalid_amount_reports_row_and_column() {
+        let mut profile = CsvProfile::new(
+            "NL00BANK0000000000",
+            Column::Name("Date".to_string()),
+            "%Y%m%d",
+            Column::Name("Amount".to_string()),
+        );
+        profile.skip_rows = 1;
+        let parser = CsvProfileParser::create_nullable(
+            "bank",
+            profile,
+            table(
+                &["Date", "Amount"],
+                &[&["20230423", "1.00"], &["20230424", "1.0O"]],
+            ),
+        );
+
+        let error = parser
+            .parse_file("mocked")
+            .expect_err("File not to be parsed");
+
+        assert_eq!(
+            error.to_string(),
+            "Row 4: Invalid amount '1.0O' in column Amount: Invalid amount '1.0O'"
+        );
+    }
+}
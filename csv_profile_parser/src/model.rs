@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+/// A column selected by its header name, or by its zero-based index.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Column::Index(index) => write!(f, "{}", index),
+            Column::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum Encoding {
+    #[default]
+    #[serde(rename = "utf-8", alias = "utf8")]
+    Utf8,
+    #[serde(rename = "iso-8859-1", alias = "latin1")]
+    Latin1,
+    #[serde(rename = "windows-1252", alias = "cp1252")]
+    Windows1252,
+}
+
+/// A column telling whether an unsigned amount is an outflow, e.g. `Af` or
+/// `Bij` in ING exports.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DirectionColumn {
+    pub column: Column,
+    pub outflow: String,
+}
+
+/// Describes the layout of a bank's CSV export. Text fields are templates in
+/// which `{column}` is replaced by the value of that column, e.g.
+/// `"{Naam / Omschrijving}"`.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CsvProfile {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default)]
+    pub encoding: Encoding,
+    #[serde(default = "default_header")]
+    pub header: bool,
+    /// Rows before the header row, or the first transaction without header.
+    #[serde(default)]
+    pub skip_rows: usize,
+    pub account: String,
+    pub date: Column,
+    /// A chrono format such as `%d-%m-%Y`.
+    pub date_format: String,
+    /// A signed amount, unless a direction column is set.
+    pub amount: Option<Column>,
+    pub direction: Option<DirectionColumn>,
+    /// Unsigned amounts, used when there is no amount column.
+    pub inflow: Option<Column>,
+    pub outflow: Option<Column>,
+    #[serde(default)]
+    pub decimal_comma: bool,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub counterparty_account: Option<String>,
+    pub reference: Option<String>,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_header() -> bool {
+    true
+}
+
+fn default_currency() -> String {
+    "EUR".to_owned()
+}
+
+impl CsvProfile {
+    pub fn new(account: &str, date: Column, date_format: &str, amount: Column) -> Self {
+        CsvProfile {
+            delimiter: default_delimiter(),
+            encoding: Encoding::default(),
+            header: default_header(),
+            skip_rows: 0,
+            account: account.to_owned(),
+            date,
+            date_format: date_format.to_owned(),
+            amount: Some(amount),
+            direction: None,
+            inflow: None,
+            outflow: None,
+            decimal_comma: false,
+            currency: default_currency(),
+            payee: None,
+            memo: None,
+            counterparty_account: None,
+            reference: None,
+        }
+    }
+}
+
+/// The header row, if the profile has one, and the rows of a CSV export.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
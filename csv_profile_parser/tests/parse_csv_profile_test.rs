@@ -0,0 +1,50 @@
+use std::fs;
+
+use chrono::NaiveDate;
+use common::{money::Money, parser::StatementParser, Entry};
+use csv_profile_parser::{model::CsvProfile, CsvProfileParser};
+
+fn ing_profile() -> CsvProfile {
+    let profile_yaml = fs::read_to_string("resources/ing.yaml").expect("File to be read");
+
+    serde_yaml::from_str(&profile_yaml).expect("profile to be valid")
+}
+
+#[test]
+fn parses_profile_export() {
+    let parser = CsvProfileParser::create("ing", ing_profile());
+    let file_contents = fs::read("resources/ing.csv").expect("File to be read");
+
+    let mut result = parser.parse(&file_contents).expect("csv file to be parsed");
+
+    let expected = Entry::new(
+        "NL00INGB0001234567".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 25).expect("date to be valid"),
+        Some("Café De Jaren".to_string()),
+        Some("Pasvolgnr: 001 25-04-2023 13:02 Transactie: 12AB34".to_string()),
+        None,
+        Some(Money::parse("12.50", "EUR").expect("amount to be valid")),
+    );
+    assert_eq!(result.remove(0), expected);
+
+    let mut expected = Entry::new(
+        "NL00INGB0001234567".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 25).expect("date to be valid"),
+        Some("Werkgever BV".to_string()),
+        Some("Salaris april €".to_string()),
+        Some(Money::parse("1500.00", "EUR").expect("amount to be valid")),
+        None,
+    );
+    expected.counterparty_account = Some("NL20INGB0000686129".to_string());
+    assert_eq!(result.remove(0), expected);
+}
+
+#[test]
+fn detects_format() {
+    let parser = CsvProfileParser::create("ing", ing_profile());
+    let file_contents = fs::read("resources/ing.csv").expect("File to be read");
+    let asn_contents = fs::read("../resources/example.csv").expect("File to be read");
+
+    assert!(parser.detect(&file_contents));
+    assert!(!parser.detect(&asn_contents));
+}
@@ -1,9 +1,12 @@
 use std::{
     fs::File,
-    io::{self, Read},
+    io::{self, BufReader, Read},
     path::Path,
 };
 
+/// Size of the buffer of [`FileReader::open_buffered`].
+const BUFFER_SIZE: usize = 64 * 1024;
+
 use self::nullables::*;
 
 pub struct FileReader {
@@ -26,12 +29,38 @@ impl FileReader {
     pub fn read_file(&self, path: &str) -> Result<String, io::Error> {
         let data_file_path = Path::new(&path);
 
-        let mut data_file = self.file.open(&data_file_path)?;
+        let mut data_file = self.file.open(data_file_path)?;
         let mut content = String::new();
         data_file.read_to_string(&mut content)?;
 
         Ok(content)
     }
+
+    /// Reads the file without requiring it to be valid UTF-8.
+    pub fn read_bytes(&self, path: &str) -> Result<Vec<u8>, io::Error> {
+        let data_file_path = Path::new(&path);
+
+        let mut data_file = self.file.open(data_file_path)?;
+        let mut content = vec![];
+        data_file.read_to_end(&mut content)?;
+
+        Ok(content)
+    }
+
+    /// Opens the file for reading in parts. The buffer holds the first
+    /// 64 KiB after `fill_buf`, so the start of the file can be inspected
+    /// without consuming it.
+    pub fn open_buffered(&self, path: &str) -> Result<BufReader<Box<dyn Read>>, io::Error> {
+        let data_file = self.file.open(Path::new(&path))?;
+
+        Ok(BufReader::with_capacity(BUFFER_SIZE, data_file.into_read()))
+    }
+}
+
+impl Default for FileReader {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 mod nullables {
@@ -39,6 +68,8 @@ mod nullables {
 
     pub trait FileReaderWrapper {
         fn read_to_string(&mut self, content: &mut String) -> Result<usize, io::Error>;
+        fn read_to_end(&mut self, content: &mut Vec<u8>) -> Result<usize, io::Error>;
+        fn into_read(self: Box<Self>) -> Box<dyn Read>;
     }
 
     struct RealFileReader {
@@ -49,6 +80,14 @@ mod nullables {
         fn read_to_string(&mut self, content: &mut String) -> Result<usize, io::Error> {
             self.file.read_to_string(content)
         }
+
+        fn read_to_end(&mut self, content: &mut Vec<u8>) -> Result<usize, io::Error> {
+            self.file.read_to_end(content)
+        }
+
+        fn into_read(self: Box<Self>) -> Box<dyn Read> {
+            Box::new(self.file)
+        }
     }
 
     struct StubbedFileReader {
@@ -71,6 +110,17 @@ mod nullables {
 
             Ok(1)
         }
+
+        fn read_to_end(&mut self, content: &mut Vec<u8>) -> Result<usize, io::Error> {
+            content.clear();
+            content.extend_from_slice(self.file_contents.as_bytes());
+
+            Ok(content.len())
+        }
+
+        fn into_read(self: Box<Self>) -> Box<dyn Read> {
+            Box::new(io::Cursor::new(self.file_contents.into_bytes()))
+        }
     }
 
     pub trait FileOpenWrapper {
@@ -97,6 +147,7 @@ mod nullables {
     }
 
     impl StubbedFileOpen {
+        #[allow(clippy::new_ret_no_self)]
         pub fn new(file_contents: &str) -> Box<dyn FileOpenWrapper> {
             let file = file_contents.to_owned();
             Box::new(StubbedFileOpen {
@@ -114,15 +165,42 @@ mod nullables {
 
 #[cfg(test)]
 mod tests {
+    use std::io::BufRead;
+
     use super::*;
 
     #[test]
     fn test_file_reader() {
-        let file_reader = FileReader::nullable(&"Test content");
+        let file_reader = FileReader::nullable("Test content");
 
-        let result = file_reader.read_file(&"some_path_to_file");
+        let result = file_reader.read_file("some_path_to_file");
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "Test content".to_string());
     }
+
+    #[test]
+    fn test_file_reader_bytes() {
+        let file_reader = FileReader::nullable("Test content");
+
+        let result = file_reader.read_bytes("some_path_to_file");
+
+        assert_eq!(result.unwrap(), b"Test content".to_vec());
+    }
+
+    #[test]
+    fn test_file_reader_buffered() {
+        let file_reader = FileReader::nullable("Test content");
+
+        let mut reader = file_reader
+            .open_buffered("some_path_to_file")
+            .expect("file to be opened");
+
+        assert_eq!(reader.fill_buf().expect("file to be read"), b"Test content");
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .expect("file to be read");
+        assert_eq!(content, "Test content");
+    }
 }
@@ -1,2 +1,2 @@
 pub mod file_reader;
-// pub mod list_files;
+pub mod list_files;
@@ -1,13 +1,15 @@
-use std::{fmt::Display, fs, io, path::PathBuf, cell::RefCell};
+use std::{cell::RefCell, fmt::Display, fs, io, path::PathBuf};
 
 #[derive(Debug)]
 pub enum ListFilesError {
     IOError(std::io::Error),
+    PatternError(glob::PatternError),
 }
 impl Display for ListFilesError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ListFilesError::IOError(io_error) => write!(f, "{}", io_error),
+            ListFilesError::PatternError(pattern_error) => write!(f, "{}", pattern_error),
         }
     }
 }
@@ -19,6 +21,18 @@ impl From<std::io::Error> for ListFilesError {
     }
 }
 
+impl From<glob::PatternError> for ListFilesError {
+    fn from(err: glob::PatternError) -> Self {
+        ListFilesError::PatternError(err)
+    }
+}
+
+impl From<glob::GlobError> for ListFilesError {
+    fn from(err: glob::GlobError) -> Self {
+        ListFilesError::IOError(err.into())
+    }
+}
+
 pub struct ListFiles {
     list_files_wrapper: Box<dyn ListFilesWrapper>,
 }
@@ -38,6 +52,11 @@ impl ListFiles {
     pub fn list_files(&self, dir: &str) -> Result<Vec<PathBuf>, ListFilesError> {
         self.list_files_wrapper.list_files(dir)
     }
+
+    /// Lists the paths matching a glob pattern such as `statements/*.xml`.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<PathBuf>, ListFilesError> {
+        self.list_files_wrapper.glob(pattern)
+    }
 }
 impl Default for ListFiles {
     fn default() -> Self {
@@ -47,6 +66,7 @@ impl Default for ListFiles {
 
 trait ListFilesWrapper {
     fn list_files(&self, dir: &str) -> Result<Vec<PathBuf>, ListFilesError>;
+    fn glob(&self, pattern: &str) -> Result<Vec<PathBuf>, ListFilesError>;
 }
 
 struct RealListFiles {}
@@ -58,6 +78,10 @@ impl ListFilesWrapper for RealListFiles {
             .map(|entry| entry.path())
             .collect::<Vec<_>>())
     }
+
+    fn glob(&self, pattern: &str) -> Result<Vec<PathBuf>, ListFilesError> {
+        Ok(glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?)
+    }
 }
 
 struct StubbedListFiles {
@@ -70,7 +94,14 @@ impl StubbedListFiles {
 }
 impl ListFilesWrapper for StubbedListFiles {
     fn list_files(&self, _dir: &str) -> Result<Vec<PathBuf>, ListFilesError> {
-        *self.stubbed_result.borrow()
+        match &*self.stubbed_result.borrow() {
+            Ok(paths) => Ok(paths.to_owned()),
+            Err(err) => Err(io::Error::other(err.to_string()).into()),
+        }
+    }
+
+    fn glob(&self, pattern: &str) -> Result<Vec<PathBuf>, ListFilesError> {
+        self.list_files(pattern)
     }
 }
 
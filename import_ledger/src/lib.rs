@@ -0,0 +1,388 @@
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use common::{normalize_account, Entry};
+use output_tracker::{OutputListener, OutputTracker};
+
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("Error accessing the import ledger")]
+    IOError(#[from] io::Error),
+    #[error("Invalid import ledger record on line {line}")]
+    InvalidRecord {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Error serializing import ledger record")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+/// An imported transaction, stored as one JSON object per line.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct LedgerRecord {
+    pub account: String,
+    pub import_id: String,
+}
+
+impl LedgerRecord {
+    pub fn new(account: &str, import_id: &str) -> Self {
+        LedgerRecord {
+            account: account.to_owned(),
+            import_id: import_id.to_owned(),
+        }
+    }
+}
+
+/// Remembers which transactions were imported before, per account, so later
+/// runs only output new ones.
+pub struct ImportLedger {
+    output_listener: OutputListener<Vec<LedgerRecord>>,
+    store: Box<dyn LedgerStore>,
+}
+
+impl ImportLedger {
+    pub fn create_nullable(records: Vec<LedgerRecord>) -> ImportLedger {
+        ImportLedger {
+            output_listener: OutputListener::new(),
+            store: Box::new(StubbedLedgerStore {
+                contents: RefCell::new(
+                    records
+                        .iter()
+                        .map(|r| serde_json::to_string(r).expect("record to be serialized") + "\n")
+                        .collect(),
+                ),
+            }),
+        }
+    }
+
+    pub fn create(path: PathBuf) -> ImportLedger {
+        ImportLedger {
+            output_listener: OutputListener::new(),
+            store: Box::new(RealLedgerStore { path }),
+        }
+    }
+
+    /// Tracks the records added by `record`.
+    pub fn track_output(&mut self) -> Rc<OutputTracker<Vec<LedgerRecord>>> {
+        self.output_listener.create_tracker()
+    }
+
+    pub fn records(&self) -> Result<Vec<LedgerRecord>, LedgerError> {
+        self.store
+            .read()?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|source| LedgerError::InvalidRecord {
+                    line: index + 1,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Drops the entries that were imported before. Entries without an
+    /// import ID are always kept. Accounts match however they're spaced or
+    /// cased.
+    pub fn filter_new(&self, entries: Vec<Entry>) -> Result<Vec<Entry>, LedgerError> {
+        let records = self
+            .records()?
+            .into_iter()
+            .map(|r| LedgerRecord::new(&normalize_account(&r.account), &r.import_id))
+            .collect::<HashSet<_>>();
+
+        Ok(entries
+            .into_iter()
+            .filter(|e| {
+                e.import_id.as_ref().is_none_or(|id| {
+                    !records.contains(&LedgerRecord::new(&normalize_account(&e.account), id))
+                })
+            })
+            .collect())
+    }
+
+    /// Adds the entries with an import ID, under their normalized account.
+    pub fn record(&self, entries: &[Entry]) -> Result<(), LedgerError> {
+        let records = entries
+            .iter()
+            .filter_map(|e| {
+                e.import_id
+                    .as_ref()
+                    .map(|id| LedgerRecord::new(&normalize_account(&e.account), id))
+            })
+            .collect::<Vec<_>>();
+
+        self.output_listener.track(&records);
+
+        let mut contents = String::new();
+        for record in records.iter() {
+            contents.push_str(&serde_json::to_string(record)?);
+            contents.push('\n');
+        }
+        self.store.append(&contents)?;
+
+        Ok(())
+    }
+
+    /// Removes an import ID so the transaction is output again. Returns the
+    /// number of removed records.
+    pub fn forget(&self, import_id: &str, account: Option<&str>) -> Result<usize, LedgerError> {
+        self.remove(|r| {
+            r.import_id.eq(import_id)
+                && account.is_none_or(|a| normalize_account(&r.account) == normalize_account(a))
+        })
+    }
+
+    /// Removes every record, or those of one account. Returns the number of
+    /// removed records.
+    pub fn reset(&self, account: Option<&str>) -> Result<usize, LedgerError> {
+        self.remove(|r| {
+            account.is_none_or(|a| normalize_account(&r.account) == normalize_account(a))
+        })
+    }
+
+    fn remove(&self, predicate: impl Fn(&LedgerRecord) -> bool) -> Result<usize, LedgerError> {
+        let records = self.records()?;
+        let count = records.len();
+
+        let mut contents = String::new();
+        let mut kept = 0;
+        for record in records.iter().filter(|r| !predicate(r)) {
+            contents.push_str(&serde_json::to_string(record)?);
+            contents.push('\n');
+            kept += 1;
+        }
+        self.store.write(&contents)?;
+
+        Ok(count - kept)
+    }
+}
+
+trait LedgerStore {
+    fn read(&self) -> Result<String, io::Error>;
+    fn append(&self, contents: &str) -> Result<(), io::Error>;
+    fn write(&self, contents: &str) -> Result<(), io::Error>;
+}
+
+struct RealLedgerStore {
+    path: PathBuf,
+}
+
+impl RealLedgerStore {
+    fn create_parent_dir(&self) -> Result<(), io::Error> {
+        match self.path.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+    }
+}
+
+impl LedgerStore for RealLedgerStore {
+    fn read(&self) -> Result<String, io::Error> {
+        match fs::read_to_string(&self.path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            result => result,
+        }
+    }
+
+    fn append(&self, contents: &str) -> Result<(), io::Error> {
+        self.create_parent_dir()?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(contents.as_bytes())
+    }
+
+    fn write(&self, contents: &str) -> Result<(), io::Error> {
+        self.create_parent_dir()?;
+
+        fs::write(&self.path, contents)
+    }
+}
+
+struct StubbedLedgerStore {
+    contents: RefCell<String>,
+}
+
+impl LedgerStore for StubbedLedgerStore {
+    fn read(&self) -> Result<String, io::Error> {
+        Ok(self.contents.borrow().to_owned())
+    }
+
+    fn append(&self, contents: &str) -> Result<(), io::Error> {
+        self.contents.borrow_mut().push_str(contents);
+        Ok(())
+    }
+
+    fn write(&self, contents: &str) -> Result<(), io::Error> {
+        self.contents.replace(contents.to_owned());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn entry(account: &str, import_id: &str) -> Entry {
+        let mut entry = Entry::new(
+            account.to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 1).expect("date to be valid"),
+            None,
+            None,
+            None,
+            None,
+        );
+        entry.import_id = Some(import_id.to_string());
+        entry
+    }
+
+    #[test]
+    fn filters_imported_entries_per_account() {
+        let ledger = ImportLedger::create_nullable(vec![LedgerRecord::new("main", "YNAB:1")]);
+
+        let result = ledger
+            .filter_new(vec![
+                entry("main", "YNAB:1"),
+                entry("main", "YNAB:2"),
+                entry("savings", "YNAB:1"),
+            ])
+            .expect("entries to be filtered");
+
+        assert_eq!(
+            result,
+            vec![entry("main", "YNAB:2"), entry("savings", "YNAB:1")]
+        );
+    }
+
+    #[test]
+    fn records_entries() {
+        let mut ledger = ImportLedger::create_nullable(vec![]);
+        let tracker = ledger.track_output();
+
+        ledger
+            .record(&[entry("NL00BANK0000000000", "YNAB:1")])
+            .expect("entries to be recorded");
+
+        assert_eq!(
+            tracker.flush(),
+            vec![vec![LedgerRecord::new("NL00BANK0000000000", "YNAB:1")]]
+        );
+        assert_eq!(
+            ledger
+                .filter_new(vec![entry("NL00BANK0000000000", "YNAB:1")])
+                .expect("entries to be filtered"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn matches_accounts_however_they_are_spaced() {
+        let mut ledger = ImportLedger::create_nullable(vec![LedgerRecord::new(
+            "nl00 bank 0000 0000 00",
+            "YNAB:1",
+        )]);
+        let tracker = ledger.track_output();
+
+        let result = ledger
+            .filter_new(vec![
+                entry("NL00BANK0000000000", "YNAB:1"),
+                entry("NL00 BANK 0000 0000 00", "YNAB:2"),
+            ])
+            .expect("entries to be filtered");
+        ledger.record(&result).expect("entries to be recorded");
+
+        assert_eq!(result, vec![entry("NL00 BANK 0000 0000 00", "YNAB:2")]);
+        assert_eq!(
+            tracker.flush(),
+            vec![vec![LedgerRecord::new("NL00BANK0000000000", "YNAB:2")]]
+        );
+    }
+
+    #[test]
+    fn forgets_import_id() {
+        let ledger = ImportLedger::create_nullable(vec![
+            LedgerRecord::new("main", "YNAB:1"),
+            LedgerRecord::new("main", "YNAB:2"),
+            LedgerRecord::new("savings", "YNAB:1"),
+        ]);
+
+        let removed = ledger
+            .forget("YNAB:1", Some("main"))
+            .expect("import id to be forgotten");
+
+        assert_eq!(removed, 1);
+        assert_eq!(
+            ledger.records().expect("records to be read"),
+            vec![
+                LedgerRecord::new("main", "YNAB:2"),
+                LedgerRecord::new("savings", "YNAB:1")
+            ]
+        );
+    }
+
+    #[test]
+    fn resets_account() {
+        let ledger = ImportLedger::create_nullable(vec![
+            LedgerRecord::new("main", "YNAB:1"),
+            LedgerRecord::new("savings", "YNAB:1"),
+        ]);
+
+        let removed = ledger.reset(Some("main")).expect("ledger to be reset");
+
+        assert_eq!(removed, 1);
+        assert_eq!(
+            ledger.records().expect("records to be read"),
+            vec![LedgerRecord::new("savings", "YNAB:1")]
+        );
+    }
+
+    #[test]
+    fn forgets_import_id_however_account_is_spaced() {
+        let ledger = ImportLedger::create_nullable(vec![
+            LedgerRecord::new("NL00BANK0000000000", "YNAB:1"),
+            LedgerRecord::new("NL00BANK0000000001", "YNAB:1"),
+        ]);
+
+        let removed = ledger
+            .forget("YNAB:1", Some("nl00 bank 0000 0000 00"))
+            .expect("import id to be forgotten");
+
+        assert_eq!(removed, 1);
+        assert_eq!(
+            ledger.records().expect("records to be read"),
+            vec![LedgerRecord::new("NL00BANK0000000001", "YNAB:1")]
+        );
+    }
+
+    #[test]
+    fn fails_on_invalid_record() {
+        let ledger = ImportLedger::create_nullable(vec![]);
+        ledger
+            .store
+            .append("{\"account\":\"main\",\"import_id\":\"YNAB:1\"}\nnot json\n")
+            .expect("contents to be appended");
+
+        let result = ledger.records();
+
+        assert!(matches!(
+            result,
+            Err(LedgerError::InvalidRecord { line: 2, .. })
+        ));
+    }
+}
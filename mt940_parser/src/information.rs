@@ -0,0 +1,162 @@
+/// Codes of the structured :86: format used by Dutch banks, e.g.
+/// `/TRTP/SEPA OVERBOEKING/IBAN/NL12RABO0123456789/NAME/J DOE/REMI/Invoice/`.
+const CODES: [&str; 24] = [
+    "TRTP",
+    "IBAN",
+    "BIC",
+    "NAME",
+    "REMI",
+    "EREF",
+    "MARF",
+    "CSID",
+    "ORDP",
+    "BENM",
+    "ID",
+    "ADDR",
+    "PURP",
+    "CDTRREF",
+    "CDTRREFTP",
+    "ULTC",
+    "ULTD",
+    "ISDT",
+    "RTRN",
+    "SVCL",
+    "CNTP",
+    "ACCW",
+    "EXCH",
+    "CHGS",
+];
+
+/// The details of a :86: field that end up in an `Entry`.
+#[derive(Debug, PartialEq, Default)]
+pub struct Information {
+    pub name: Option<String>,
+    pub iban: Option<String>,
+    pub bic: Option<String>,
+    pub remittance: Option<String>,
+    pub ultimate_debtor: Option<String>,
+    pub ultimate_creditor: Option<String>,
+}
+
+/// Splits structured information into `(code, value)` pairs. Values may
+/// contain slashes, so only known codes start a new pair.
+fn structured_fields(text: &str) -> Vec<(&str, &str)> {
+    let mut starts = vec![];
+    for (index, _) in text.match_indices('/') {
+        let code = CODES.iter().find(|code| {
+            text[index + 1..]
+                .strip_prefix(**code)
+                .is_some_and(|rest| rest.starts_with('/'))
+        });
+        if let Some(code) = code {
+            let overlaps = starts
+                .last()
+                .is_some_and(|(start, code): &(usize, &str)| index < start + code.len() + 2);
+            if !overlaps {
+                starts.push((index, *code));
+            }
+        }
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, (start, code))| {
+            let end = starts.get(i + 1).map(|(s, _)| *s).unwrap_or(text.len());
+            let value = &text[start + code.len() + 2..end.max(start + code.len() + 2)];
+            (*code, value.trim_end_matches('/'))
+        })
+        .collect()
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+/// Parses structured information. Returns `None` for free text.
+pub fn parse_information(text: &str) -> Option<Information> {
+    if !text.starts_with('/') {
+        return None;
+    }
+    let fields = structured_fields(text);
+    if fields.is_empty() {
+        return None;
+    }
+
+    let mut information = Information::default();
+    for (code, value) in fields {
+        match code {
+            "NAME" => information.name = non_empty(value),
+            "IBAN" => information.iban = non_empty(value),
+            "BIC" => information.bic = non_empty(value),
+            "REMI" => {
+                let value = value
+                    .strip_prefix("USTD//")
+                    .or(value.strip_prefix("STRD/CUR/"))
+                    .unwrap_or(value);
+                information.remittance = non_empty(value);
+            }
+            "ULTD" => information.ultimate_debtor = value.split('/').next().and_then(non_empty),
+            "ULTC" => information.ultimate_creditor = value.split('/').next().and_then(non_empty),
+            "CNTP" => {
+                let mut parts = value.split('/');
+                information.iban = information.iban.or(parts.next().and_then(non_empty));
+                information.bic = information.bic.or(parts.next().and_then(non_empty));
+                information.name = information.name.or(parts.next().and_then(non_empty));
+            }
+            _ => {}
+        }
+    }
+
+    Some(information)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_structured_information() {
+        let result = parse_information(
+            "/TRTP/SEPA OVERBOEKING/IBAN/NL12RABO0123456789/BIC/RABONL2U/NAME/J DOE/REMI/Invoice 1/2/EREF/NOTPROVIDED",
+        );
+
+        assert_eq!(
+            result,
+            Some(Information {
+                name: Some("J DOE".to_string()),
+                iban: Some("NL12RABO0123456789".to_string()),
+                bic: Some("RABONL2U".to_string()),
+                remittance: Some("Invoice 1/2".to_string()),
+                ultimate_debtor: None,
+                ultimate_creditor: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_counterparty_and_ultimate_parties() {
+        let result = parse_information(
+            "/CNTP/NL12RABO0123456789/RABONL2U/J DOE/AMSTERDAM/REMI/USTD//Rent/ULTD/Jane Doe/",
+        );
+
+        assert_eq!(
+            result,
+            Some(Information {
+                name: Some("J DOE".to_string()),
+                iban: Some("NL12RABO0123456789".to_string()),
+                bic: Some("RABONL2U".to_string()),
+                remittance: Some("Rent".to_string()),
+                ultimate_debtor: Some("Jane Doe".to_string()),
+                ultimate_creditor: None,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_free_text() {
+        assert_eq!(parse_information("ALBERT HEIJN 1631 GRONINGEN"), None);
+        assert_eq!(parse_information("/ unknown /"), None);
+    }
+}
@@ -0,0 +1,370 @@
+mod information;
+pub mod model;
+mod syntax;
+use chrono::{Datelike, NaiveDate};
+use thiserror::Error;
+
+use crate::{
+    information::parse_information,
+    model::{Balance, Mark, Message, StatementLine},
+};
+use common::{
+    money::{Money, MoneyError},
+    parser::{ParseStatementError, StatementParser, SNIFF_LENGTH},
+    split_payee_memo, Entry, Statement,
+};
+
+#[derive(Error, Debug)]
+pub enum ParseMt940Error {
+    #[error("Missing :{0}: field")]
+    MissingField(String),
+    #[error("Invalid :{tag}: field '{value}'")]
+    InvalidField { tag: String, value: String },
+    #[error("Invalid amount '{value}' in :{tag}: field: {source}")]
+    AmountError {
+        tag: String,
+        value: String,
+        source: MoneyError,
+    },
+    #[error("Invalid date '{0}'")]
+    DateError(String),
+    #[error("Statement line {line} of {account}: {source}")]
+    InvalidLine {
+        account: String,
+        line: usize,
+        source: Box<ParseMt940Error>,
+    },
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, ParseMt940Error> {
+    NaiveDate::parse_from_str(date, "%y%m%d")
+        .map_err(|_| ParseMt940Error::DateError(date.to_owned()))
+}
+
+/// The entry date only has a month and day. It is booked close to the value
+/// date, so the year is the one that puts both dates nearest together.
+fn parse_entry_date(value_date: NaiveDate, entry_date: &str) -> Result<NaiveDate, ParseMt940Error> {
+    let invalid = || ParseMt940Error::DateError(entry_date.to_owned());
+    let month = entry_date
+        .get(..2)
+        .and_then(|m| m.parse().ok())
+        .ok_or_else(invalid)?;
+    let day = entry_date
+        .get(2..)
+        .and_then(|d| d.parse().ok())
+        .ok_or_else(invalid)?;
+
+    [
+        value_date.year() - 1,
+        value_date.year(),
+        value_date.year() + 1,
+    ]
+    .into_iter()
+    .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+    .min_by_key(|date| (*date - value_date).num_days().abs())
+    .ok_or_else(invalid)
+}
+
+/// Parses an amount with a decimal comma such as `12,50` or `12,` from the
+/// field with the tag.
+fn parse_amount(tag: &str, amount: &str, currency: &str) -> Result<Money, ParseMt940Error> {
+    let decimal = amount.replace(',', ".");
+    let decimal = match decimal.strip_suffix('.') {
+        Some(units) => units.to_owned(),
+        None => decimal,
+    };
+
+    Money::parse(&decimal, currency).map_err(|source| ParseMt940Error::AmountError {
+        tag: tag.to_owned(),
+        value: amount.to_owned(),
+        source,
+    })
+}
+
+fn parse_balance(tag: &str, balance: &Balance) -> Result<Money, ParseMt940Error> {
+    let mut money = parse_amount(tag, &balance.amount, &balance.currency)?;
+    if balance.mark == Mark::Debit {
+        money.amount = -money.amount;
+    }
+
+    Ok(money)
+}
+
+fn convert_line(
+    account: &str,
+    currency: &str,
+    line: StatementLine,
+) -> Result<Entry, ParseMt940Error> {
+    let value_date = parse_date(&line.value_date)?;
+    let date = match line.entry_date.as_ref() {
+        Some(entry_date) => parse_entry_date(value_date, entry_date)?,
+        None => value_date,
+    };
+
+    let mut entry = Entry::new(account.to_owned(), date, None, None, None, None);
+    entry.bank_transaction_code = Some(line.transaction_type);
+    entry.reference = line
+        .bank_reference
+        .filter(|r| !r.is_empty())
+        .or(Some(line.customer_reference).filter(|r| !r.is_empty() && r.ne("NONREF")));
+
+    let text = line.information.unwrap_or_default();
+    let (payee, memo) = match parse_information(&text) {
+        Some(information) => {
+            entry.counterparty_account = information.iban;
+            entry.counterparty_bic = information.bic;
+            entry.ultimate_debtor = information.ultimate_debtor;
+            entry.ultimate_creditor = information.ultimate_creditor;
+            (information.name, information.remittance)
+        }
+        None => (None, Some(text).filter(|t| !t.trim().is_empty())),
+    };
+
+    let (payee, memo) = split_payee_memo(payee, memo);
+
+    entry.payee = payee;
+    entry.memo = memo.map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    let amount = parse_amount("61", &line.amount, currency)?;
+    match line.mark {
+        Mark::Credit | Mark::ReversalDebit => entry.inflow = Some(amount),
+        Mark::Debit | Mark::ReversalCredit => entry.outflow = Some(amount),
+    }
+    entry.reversal = matches!(line.mark, Mark::ReversalCredit | Mark::ReversalDebit);
+
+    Ok(entry)
+}
+
+fn convert_message(message: Message) -> Result<Statement, ParseMt940Error> {
+    let currency = message.opening_balance.currency.to_owned();
+    // Some banks append the currency to the account, e.g. `NL20INGB0001234567EUR`.
+    let account = message
+        .account
+        .strip_suffix(currency.as_str())
+        .filter(|a| !a.is_empty())
+        .unwrap_or(&message.account)
+        .to_owned();
+
+    let mut statement = Statement {
+        account: account.to_owned(),
+        opening_balance: Some(parse_balance("60F", &message.opening_balance)?),
+        closing_balance: Some(parse_balance("62F", &message.closing_balance)?),
+        entries: vec![],
+    };
+    for (index, line) in message.lines.into_iter().enumerate() {
+        let entry =
+            convert_line(&account, &currency, line).map_err(|e| ParseMt940Error::InvalidLine {
+                account: account.to_owned(),
+                line: index + 1,
+                source: Box::new(e),
+            })?;
+        statement.entries.push(entry);
+    }
+
+    Ok(statement)
+}
+
+pub struct Mt940Parser {
+    mt940_reader: Box<dyn Mt940Reader>,
+}
+
+impl Mt940Parser {
+    pub fn create_nullable(messages: Vec<Message>) -> Self {
+        Mt940Parser {
+            mt940_reader: Box::new(StubbedMt940Reader { messages }),
+        }
+    }
+
+    pub fn create() -> Self {
+        Mt940Parser {
+            mt940_reader: Box::new(RealMt940Reader {}),
+        }
+    }
+
+    pub fn parse_file(&self, mt940_contents: &str) -> Result<Vec<Entry>, ParseMt940Error> {
+        Ok(self
+            .parse_statements(mt940_contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+
+    pub fn parse_statements(
+        &self,
+        mt940_contents: &str,
+    ) -> Result<Vec<Statement>, ParseMt940Error> {
+        self.mt940_reader
+            .read_from_str(mt940_contents)?
+            .into_iter()
+            .map(convert_message)
+            .collect()
+    }
+}
+
+impl StatementParser for Mt940Parser {
+    fn name(&self) -> &str {
+        "mt940"
+    }
+
+    fn detect(&self, contents: &[u8]) -> bool {
+        let head = String::from_utf8_lossy(&contents[..contents.len().min(SNIFF_LENGTH)]);
+
+        head.lines().any(|l| l.starts_with(":20:"))
+            && head.lines().any(|l| l.starts_with(":25:"))
+            && head
+                .lines()
+                .any(|l| l.starts_with(":60F:") || l.starts_with(":60M:"))
+    }
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError> {
+        Mt940Parser::parse_statements(self, std::str::from_utf8(contents)?)
+            .map_err(ParseStatementError::parser)
+    }
+}
+
+trait Mt940Reader {
+    fn read_from_str(&self, mt940_contents: &str) -> Result<Vec<Message>, ParseMt940Error>;
+}
+
+struct RealMt940Reader {}
+
+impl Mt940Reader for RealMt940Reader {
+    fn read_from_str(&self, mt940_contents: &str) -> Result<Vec<Message>, ParseMt940Error> {
+        syntax::read_messages(mt940_contents)
+    }
+}
+
+struct StubbedMt940Reader {
+    messages: Vec<Message>,
+}
+
+impl Mt940Reader for StubbedMt940Reader {
+    fn read_from_str(&self, _mt940_contents: &str) -> Result<Vec<Message>, ParseMt940Error> {
+        Ok(self.messages.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(lines: Vec<StatementLine>) -> Message {
+        Message {
+            transaction_reference: "1".to_string(),
+            account: "NL81ASNB9999999999EUR".to_string(),
+            statement_number: None,
+            opening_balance: Balance::new(Mark::Credit, "230401", "100,00"),
+            lines,
+            closing_balance: Balance::new(Mark::Credit, "230405", "110,00"),
+        }
+    }
+
+    #[test]
+    fn test_empty_mt940_parser() {
+        let mt940_parser = Mt940Parser::create_nullable(vec![]);
+
+        assert_eq!(
+            mt940_parser
+                .parse_file("mocked")
+                .expect("File to be parsed"),
+            vec![]
+        )
+    }
+
+    #[test]
+    fn test_messages_have_entries() {
+        let mut line = StatementLine::new(
+            "230403",
+            Mark::Debit,
+            "12,5",
+            Some("/TRTP/SEPA OVERBOEKING/IBAN/NL12RABO0123456789/BIC/RABONL2U/NAME/J DOE/REMI/Invoice  123/ULTC/Jane Doe/".to_string()),
+        );
+        line.entry_date = Some("0404".to_string());
+        line.bank_reference = Some("B3D03KR1AB2C".to_string());
+        let mt940_parser = Mt940Parser::create_nullable(vec![message(vec![
+            line,
+            StatementLine::new(
+                "230405",
+                Mark::Credit,
+                "22,50",
+                Some("ALBERT HEIJN 1631 >GRONINGEN".to_string()),
+            ),
+        ])]);
+
+        let entries = mt940_parser
+            .parse_file("mocked")
+            .expect("File to be parsed");
+
+        let mut expected = Entry::new(
+            "NL81ASNB9999999999".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 4).expect("date to be valid"),
+            Some("J DOE".to_string()),
+            Some("Invoice 123".to_string()),
+            None,
+            Some(Money::parse("12.5", "EUR").expect("amount to be valid")),
+        );
+        expected.counterparty_account = Some("NL12RABO0123456789".to_string());
+        expected.counterparty_bic = Some("RABONL2U".to_string());
+        expected.ultimate_creditor = Some("Jane Doe".to_string());
+        expected.bank_transaction_code = Some("NTRF".to_string());
+        expected.reference = Some("B3D03KR1AB2C".to_string());
+        assert_eq!(entries[0], expected);
+
+        let mut expected = Entry::new(
+            "NL81ASNB9999999999".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 5).expect("date to be valid"),
+            Some("ALBERT HEIJN 1631".to_string()),
+            Some("GRONINGEN".to_string()),
+            Some(Money::parse("22.50", "EUR").expect("amount to be valid")),
+            None,
+        );
+        expected.bank_transaction_code = Some("NTRF".to_string());
+        assert_eq!(entries[1], expected);
+    }
+
+    #[test]
+    fn test_messages_have_statement_balances() {
+        let mt940_parser = Mt940Parser::create_nullable(vec![message(vec![
+            StatementLine::new("230403", Mark::Credit, "15,", None),
+            StatementLine::new("230404", Mark::ReversalCredit, "5,00", None),
+        ])]);
+
+        let statements = mt940_parser
+            .parse_statements("mocked")
+            .expect("File to be parsed");
+
+        assert_eq!(
+            statements[0].opening_balance,
+            Some(Money::parse("100.00", "EUR").expect("amount to be valid"))
+        );
+        assert!(statements[0].entries[1].reversal);
+        assert_eq!(statements[0].reconcile(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_amount_reports_line_and_value() {
+        let mt940_parser = Mt940Parser::create_nullable(vec![message(vec![
+            StatementLine::new("230403", Mark::Credit, "15,", None),
+            StatementLine::new("230404", Mark::Debit, "5,0O", None),
+        ])]);
+
+        let error = mt940_parser
+            .parse_statements("mocked")
+            .expect_err("File not to be parsed");
+
+        assert_eq!(
+            error.to_string(),
+            "Statement line 2 of NL81ASNB9999999999: Invalid amount '5,0O' in :61: field: Invalid amount '5.0O'"
+        );
+    }
+
+    #[test]
+    fn test_entry_date_across_year_boundary() {
+        let value_date = NaiveDate::from_ymd_opt(2022, 12, 31).expect("date to be valid");
+
+        assert_eq!(
+            parse_entry_date(value_date, "0102").expect("date to be parsed"),
+            NaiveDate::from_ymd_opt(2023, 1, 2).expect("date to be valid")
+        );
+    }
+}
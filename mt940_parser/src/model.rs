@@ -0,0 +1,81 @@
+/// Debit/credit mark of a balance or statement line. Reversals are only
+/// used on statement lines.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mark {
+    Credit,
+    Debit,
+    /// Reversal of a credit, which debits the account.
+    ReversalCredit,
+    /// Reversal of a debit, which credits the account.
+    ReversalDebit,
+}
+
+/// Tag :60F:/:60M: or :62F:/:62M:.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Balance {
+    pub mark: Mark,
+    /// `YYMMDD`
+    pub date: String,
+    pub currency: String,
+    /// Amount with a decimal comma, e.g. `1234,56`.
+    pub amount: String,
+}
+
+impl Balance {
+    pub fn new(mark: Mark, date: &str, amount: &str) -> Self {
+        Balance {
+            mark,
+            date: date.to_string(),
+            currency: "EUR".to_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+/// Tag :61: together with the :86: that follows it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StatementLine {
+    /// `YYMMDD`
+    pub value_date: String,
+    /// `MMDD`
+    pub entry_date: Option<String>,
+    pub mark: Mark,
+    pub amount: String,
+    /// Transaction type identification code, e.g. `NTRF`.
+    pub transaction_type: String,
+    pub customer_reference: String,
+    pub bank_reference: Option<String>,
+    pub supplementary_details: Option<String>,
+    /// Information to account owner, with continuation lines joined.
+    pub information: Option<String>,
+}
+
+impl StatementLine {
+    pub fn new(value_date: &str, mark: Mark, amount: &str, information: Option<String>) -> Self {
+        StatementLine {
+            value_date: value_date.to_string(),
+            entry_date: None,
+            mark,
+            amount: amount.to_string(),
+            transaction_type: "NTRF".to_string(),
+            customer_reference: "NONREF".to_string(),
+            bank_reference: None,
+            supplementary_details: None,
+            information,
+        }
+    }
+}
+
+/// One statement message, from :20: up to the closing balance.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Message {
+    /// Tag :20:
+    pub transaction_reference: String,
+    /// Tag :25:
+    pub account: String,
+    /// Tag :28C:
+    pub statement_number: Option<String>,
+    pub opening_balance: Balance,
+    pub lines: Vec<StatementLine>,
+    pub closing_balance: Balance,
+}
@@ -0,0 +1,262 @@
+use std::mem;
+
+use crate::{
+    model::{Balance, Mark, Message, StatementLine},
+    ParseMt940Error,
+};
+
+/// Splits a field line such as `:61:2304030403D12,50NTRFNONREF` into its tag
+/// and content.
+fn field_start(line: &str) -> Option<(&str, &str)> {
+    let (tag, content) = line.strip_prefix(':')?.split_once(':')?;
+
+    let valid = (2..=3).contains(&tag.len())
+        && tag.bytes().take(2).all(|b| b.is_ascii_digit())
+        && tag.bytes().skip(2).all(|b| b.is_ascii_uppercase());
+
+    valid.then_some((tag, content))
+}
+
+fn is_message_end(line: &str) -> bool {
+    line.trim_end() == "-" || line.starts_with("-}")
+}
+
+/// Reads every statement message. Lines outside of fields, such as SWIFT
+/// block headers, are ignored.
+pub fn read_messages(text: &str) -> Result<Vec<Message>, ParseMt940Error> {
+    let mut messages = vec![];
+    let mut fields: Vec<(String, String)> = vec![];
+
+    for line in text.lines() {
+        if let Some((tag, content)) = field_start(line) {
+            if tag == "20" && !fields.is_empty() {
+                messages.push(build_message(mem::take(&mut fields))?);
+            }
+            fields.push((tag.to_owned(), content.to_owned()));
+        } else if is_message_end(line) {
+            if !fields.is_empty() {
+                messages.push(build_message(mem::take(&mut fields))?);
+            }
+        } else if let Some((_, content)) = fields.last_mut() {
+            content.push('\n');
+            content.push_str(line);
+        }
+    }
+
+    if !fields.is_empty() {
+        messages.push(build_message(fields)?);
+    }
+
+    Ok(messages)
+}
+
+fn build_message(fields: Vec<(String, String)>) -> Result<Message, ParseMt940Error> {
+    let mut transaction_reference = None;
+    let mut account = None;
+    let mut statement_number = None;
+    let mut opening_balance = None;
+    let mut closing_balance = None;
+    let mut lines: Vec<StatementLine> = vec![];
+    let mut previous_tag = String::new();
+
+    for (tag, content) in fields {
+        match tag.as_str() {
+            "20" => transaction_reference = Some(content.trim().to_owned()),
+            "25" => account = content.split_whitespace().next().map(|a| a.to_owned()),
+            "28" | "28C" => statement_number = Some(content.trim().to_owned()),
+            "60F" | "60M" => opening_balance = Some(parse_balance(&tag, &content)?),
+            "61" => lines.push(parse_statement_line(&content)?),
+            "86" if previous_tag == "61" => {
+                if let Some(line) = lines.last_mut() {
+                    line.information = Some(content.lines().collect());
+                }
+            }
+            "62F" | "62M" => closing_balance = Some(parse_balance(&tag, &content)?),
+            _ => {}
+        }
+        previous_tag = tag;
+    }
+
+    let missing = |tag: &str| ParseMt940Error::MissingField(tag.to_owned());
+
+    Ok(Message {
+        transaction_reference: transaction_reference.ok_or_else(|| missing("20"))?,
+        account: account.ok_or_else(|| missing("25"))?,
+        statement_number,
+        opening_balance: opening_balance.ok_or_else(|| missing("60F"))?,
+        lines,
+        closing_balance: closing_balance.ok_or_else(|| missing("62F"))?,
+    })
+}
+
+fn invalid(tag: &str, content: &str) -> ParseMt940Error {
+    ParseMt940Error::InvalidField {
+        tag: tag.to_owned(),
+        value: content.trim().to_owned(),
+    }
+}
+
+/// Parses a balance such as `C230401EUR1234,56`.
+fn parse_balance(tag: &str, content: &str) -> Result<Balance, ParseMt940Error> {
+    let content = content.trim();
+
+    let mark = match content.get(..1) {
+        Some("C") => Mark::Credit,
+        Some("D") => Mark::Debit,
+        _ => return Err(invalid(tag, content)),
+    };
+    let (Some(date), Some(currency), Some(amount)) =
+        (content.get(1..7), content.get(7..10), content.get(10..))
+    else {
+        return Err(invalid(tag, content));
+    };
+
+    Ok(Balance {
+        mark,
+        date: date.to_owned(),
+        currency: currency.to_owned(),
+        amount: amount.to_owned(),
+    })
+}
+
+/// Parses a statement line such as
+/// `2304030403D12,50NTRFNONREF//B3D03KR1AB2C` with optional supplementary
+/// details on the next line.
+fn parse_statement_line(content: &str) -> Result<StatementLine, ParseMt940Error> {
+    let (line, supplementary_details) = match content.split_once('\n') {
+        Some((line, details)) => (line, Some(details.trim().to_owned())),
+        None => (content, None),
+    };
+    let error = || invalid("61", line);
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    let value_date = line.get(..6).filter(|d| is_digits(d)).ok_or_else(error)?;
+    let mut rest = &line[6..];
+
+    let entry_date = rest.get(..4).filter(|d| is_digits(d));
+    if entry_date.is_some() {
+        rest = &rest[4..];
+    }
+
+    let (mark, length) = if rest.starts_with("RC") {
+        (Mark::ReversalCredit, 2)
+    } else if rest.starts_with("RD") {
+        (Mark::ReversalDebit, 2)
+    } else if rest.starts_with('C') {
+        (Mark::Credit, 1)
+    } else if rest.starts_with('D') {
+        (Mark::Debit, 1)
+    } else {
+        return Err(error());
+    };
+    rest = &rest[length..];
+
+    // Optional funds code, the third character of the currency code.
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+
+    let amount_length = rest
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(rest.len());
+    if amount_length == 0 {
+        return Err(error());
+    }
+    let amount = &rest[..amount_length];
+    rest = &rest[amount_length..];
+
+    let transaction_type = rest.get(..4).ok_or_else(error)?;
+    rest = &rest[4..];
+
+    let (customer_reference, bank_reference) = match rest.split_once("//") {
+        Some((customer, bank)) => (customer, Some(bank.trim().to_owned())),
+        None => (rest, None),
+    };
+
+    Ok(StatementLine {
+        value_date: value_date.to_owned(),
+        entry_date: entry_date.map(|d| d.to_owned()),
+        mark,
+        amount: amount.to_owned(),
+        transaction_type: transaction_type.to_owned(),
+        customer_reference: customer_reference.trim().to_owned(),
+        bank_reference,
+        supplementary_details,
+        information: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_messages() {
+        let text = "{1:F01ASNBNL21XXXX0000000000}{4:
+:20:0000000001
+:25:NL81ASNB9999999999 EUR
+:28C:1/1
+:60F:C230401EUR1000,00
+:61:2304030403D12,50NTRFNONREF//B3D03KR1AB2C
+/TRCD/01028/
+:86:/TRTP/SEPA OVERBOEKING/IBAN/NL12RABO0123456789/BIC/RABONL2U/NA
+ME/J DOE/REMI/Invoice 123/
+:61:230404RC1,NMSC0000
+:62F:C230404EUR986,50
+-}";
+
+        let messages = read_messages(text).expect("messages to be read");
+
+        assert_eq!(
+            messages,
+            vec![Message {
+                transaction_reference: "0000000001".to_string(),
+                account: "NL81ASNB9999999999".to_string(),
+                statement_number: Some("1/1".to_string()),
+                opening_balance: Balance::new(Mark::Credit, "230401", "1000,00"),
+                lines: vec![
+                    StatementLine {
+                        value_date: "230403".to_string(),
+                        entry_date: Some("0403".to_string()),
+                        mark: Mark::Debit,
+                        amount: "12,50".to_string(),
+                        transaction_type: "NTRF".to_string(),
+                        customer_reference: "NONREF".to_string(),
+                        bank_reference: Some("B3D03KR1AB2C".to_string()),
+                        supplementary_details: Some("/TRCD/01028/".to_string()),
+                        information: Some(
+                            "/TRTP/SEPA OVERBOEKING/IBAN/NL12RABO0123456789/BIC/RABONL2U/NAME/J DOE/REMI/Invoice 123/"
+                                .to_string()
+                        ),
+                    },
+                    StatementLine {
+                        value_date: "230404".to_string(),
+                        entry_date: None,
+                        mark: Mark::ReversalCredit,
+                        amount: "1,".to_string(),
+                        transaction_type: "NMSC".to_string(),
+                        customer_reference: "0000".to_string(),
+                        bank_reference: None,
+                        supplementary_details: None,
+                        information: None,
+                    },
+                ],
+                closing_balance: Balance::new(Mark::Credit, "230404", "986,50"),
+            }]
+        );
+    }
+
+    #[test]
+    fn fails_on_missing_closing_balance() {
+        let result = read_messages(":20:1\n:25:NL81ASNB9999999999\n:60F:C230401EUR1000,00\n-");
+
+        assert!(matches!(result, Err(ParseMt940Error::MissingField(tag)) if tag == "62F"));
+    }
+
+    #[test]
+    fn fails_on_invalid_statement_line() {
+        let result = read_messages(":20:1\n:25:NL81ASNB9999999999\n:61:2304X\n");
+
+        assert!(matches!(result, Err(ParseMt940Error::InvalidField { tag, .. }) if tag == "61"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs;
+
+use chrono::NaiveDate;
+use common::{money::Money, parser::StatementParser, Entry};
+use mt940_parser::Mt940Parser;
+
+#[test]
+fn name() {
+    let mt940_parser = Mt940Parser::create();
+    let file_contents = fs::read_to_string("resources/example.sta").expect("File to be read");
+
+    let mut result = mt940_parser
+        .parse_file(&file_contents)
+        .expect("mt940 file to be parsed");
+
+    let mut expected = Entry::new(
+        "NL81ASNB9999999999".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 3).expect("date to be valid"),
+        Some("BEN NEDERLAND".to_string()),
+        Some("Klant 1.50884684 Factuur 908053695899".to_string()),
+        None,
+        Some(Money::parse("12.50", "EUR").expect("amount to be valid")),
+    );
+    expected.counterparty_account = Some("NL61COBA0733974775".to_string());
+    expected.counterparty_bic = Some("COBANL2X".to_string());
+    expected.bank_transaction_code = Some("NDIV".to_string());
+    expected.reference = Some("B3D03KR1AB2C".to_string());
+    assert_eq!(result.remove(0), expected);
+
+    let mut expected = Entry::new(
+        "NL81ASNB9999999999".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 5).expect("date to be valid"),
+        Some("WERKGEVER BV".to_string()),
+        Some("Salaris april 2023".to_string()),
+        Some(Money::parse("1500.00", "EUR").expect("amount to be valid")),
+        None,
+    );
+    expected.counterparty_account = Some("NL20INGB0000686129".to_string());
+    expected.counterparty_bic = Some("INGBNL2A".to_string());
+    expected.ultimate_debtor = Some("WERKGEVER HOLDING BV".to_string());
+    expected.bank_transaction_code = Some("NTRF".to_string());
+    expected.reference = Some("B3D05SAL0001".to_string());
+    assert_eq!(result.remove(0), expected);
+
+    let expected_payee = Some("ALBERT HEIJN 1631".to_string());
+    let entry = result.remove(0);
+    assert_eq!(entry.payee, expected_payee);
+    assert_eq!(
+        entry.memo,
+        Some("GRONINGEN 07.04.2023 13U30 KV005 WW34CG MCC:5411 Contactloze betaling".to_string())
+    );
+
+    let entry = result.remove(0);
+    assert!(entry.reversal);
+    assert_eq!(
+        entry.inflow,
+        Some(Money::parse("12.50", "EUR").expect("amount to be valid"))
+    );
+}
+
+#[test]
+fn statements_reconcile() {
+    let mt940_parser = Mt940Parser::create();
+    let file_contents = fs::read_to_string("resources/example.sta").expect("File to be read");
+
+    let statements = mt940_parser
+        .parse_statements(&file_contents)
+        .expect("mt940 file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(
+        statements[0].opening_balance,
+        Some(Money::parse("1000.00", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(
+        statements[0].closing_balance,
+        Some(Money::parse("2472.14", "EUR").expect("amount to be valid"))
+    );
+    assert_eq!(statements[0].reconcile(), Ok(()));
+}
+
+#[test]
+fn detects_format() {
+    let mt940_parser = Mt940Parser::create();
+    let mt940 = fs::read("resources/example.sta").expect("File to be read");
+    let xml = fs::read("../camt053_parser/resources/example.xml").expect("File to be read");
+
+    assert!(mt940_parser.detect(&mt940));
+    assert!(!mt940_parser.detect(&xml));
+}
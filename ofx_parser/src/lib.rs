@@ -0,0 +1,373 @@
+mod markup;
+pub mod model;
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::{
+    markup::Element,
+    model::{OfxBalance, OfxStatement, OfxTransaction},
+};
+use common::{
+    money::{Money, MoneyError},
+    parser::{ParseStatementError, StatementParser, SNIFF_LENGTH},
+    Entry, Statement,
+};
+
+#[derive(Error, Debug)]
+pub enum ParseOfxError {
+    #[error("Missing <{0}> element")]
+    MissingElement(String),
+    #[error("Invalid markup near '{0}'")]
+    InvalidMarkup(String),
+    #[error("Invalid amount '{value}' in <{element}>: {source}")]
+    AmountError {
+        element: String,
+        value: String,
+        source: MoneyError,
+    },
+    #[error("Invalid date '{0}'")]
+    DateError(String),
+    #[error("Transaction {fit_id}: {source}")]
+    InvalidTransaction {
+        fit_id: String,
+        source: Box<ParseOfxError>,
+    },
+}
+
+/// Parses the date of a datetime such as `20230403120000.000[-5:EST]`.
+fn parse_date(date: &str) -> Result<NaiveDate, ParseOfxError> {
+    date.get(..8)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+        .ok_or_else(|| ParseOfxError::DateError(date.to_owned()))
+}
+
+/// Parses a signed amount such as `-12.50`, `+12.50` or `12,50` from the
+/// element.
+fn parse_amount(element: &str, amount: &str, currency: &str) -> Result<Money, ParseOfxError> {
+    let decimal = amount.trim().trim_start_matches('+').replace(',', ".");
+    let decimal = match decimal.strip_prefix('-') {
+        Some(units) if units.starts_with('.') => format!("-0{}", units),
+        _ if decimal.starts_with('.') => format!("0{}", decimal),
+        _ => decimal,
+    };
+
+    Money::parse(&decimal, currency).map_err(|source| ParseOfxError::AmountError {
+        element: element.to_owned(),
+        value: amount.to_owned(),
+        source,
+    })
+}
+
+fn convert_transaction(
+    account: &str,
+    currency: &str,
+    transaction: OfxTransaction,
+) -> Result<Entry, ParseOfxError> {
+    let mut amount = parse_amount("TRNAMT", &transaction.trn_amt, currency)?;
+
+    let mut entry = Entry::new(
+        account.to_owned(),
+        parse_date(&transaction.dt_posted)?,
+        transaction.name.filter(|n| !n.trim().is_empty()),
+        transaction
+            .memo
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|m| !m.is_empty()),
+        None,
+        None,
+    );
+    entry.bank_transaction_code = Some(transaction.trn_type);
+    entry.reference = Some(transaction.fit_id.to_owned());
+    entry.import_id = Some(transaction.fit_id);
+
+    if amount.amount.is_sign_negative() {
+        amount.amount = -amount.amount;
+        entry.outflow = Some(amount);
+    } else {
+        entry.inflow = Some(amount);
+    }
+
+    Ok(entry)
+}
+
+/// OFX only reports the ledger balance at the end of the statement, so the
+/// opening balance is unknown and the app reports the closing balance for the
+/// user to compare instead of reconciling.
+fn convert_statement(statement: OfxStatement) -> Result<Statement, ParseOfxError> {
+    let closing_balance = statement
+        .ledger_bal
+        .map(|b| parse_amount("BALAMT", &b.bal_amt, &statement.cur_def))
+        .transpose()?;
+
+    let entries = statement
+        .transactions
+        .into_iter()
+        .map(|t| {
+            let fit_id = t.fit_id.to_owned();
+            convert_transaction(&statement.acct_id, &statement.cur_def, t).map_err(|e| {
+                ParseOfxError::InvalidTransaction {
+                    fit_id,
+                    source: Box::new(e),
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Statement {
+        account: statement.acct_id,
+        opening_balance: None,
+        closing_balance,
+        entries,
+    })
+}
+
+pub struct OfxParser {
+    ofx_reader: Box<dyn OfxReader>,
+}
+
+impl OfxParser {
+    pub fn create_nullable(statements: Vec<OfxStatement>) -> Self {
+        OfxParser {
+            ofx_reader: Box::new(StubbedOfxReader { statements }),
+        }
+    }
+
+    pub fn create() -> Self {
+        OfxParser {
+            ofx_reader: Box::new(RealOfxReader {}),
+        }
+    }
+
+    pub fn parse_file(&self, ofx_contents: &str) -> Result<Vec<Entry>, ParseOfxError> {
+        Ok(self
+            .parse_statements(ofx_contents)?
+            .into_iter()
+            .flat_map(|s| s.entries)
+            .collect())
+    }
+
+    pub fn parse_statements(&self, ofx_contents: &str) -> Result<Vec<Statement>, ParseOfxError> {
+        self.ofx_reader
+            .read_from_str(ofx_contents)?
+            .into_iter()
+            .map(convert_statement)
+            .collect()
+    }
+}
+
+impl StatementParser for OfxParser {
+    fn name(&self) -> &str {
+        "ofx"
+    }
+
+    fn detect(&self, contents: &[u8]) -> bool {
+        let head = String::from_utf8_lossy(&contents[..contents.len().min(SNIFF_LENGTH)])
+            .to_ascii_uppercase();
+
+        head.contains("OFXHEADER") || head.contains("<OFX>")
+    }
+
+    fn parse_statements(&self, contents: &[u8]) -> Result<Vec<Statement>, ParseStatementError> {
+        OfxParser::parse_statements(self, std::str::from_utf8(contents)?)
+            .map_err(ParseStatementError::parser)
+    }
+}
+
+trait OfxReader {
+    fn read_from_str(&self, ofx_contents: &str) -> Result<Vec<OfxStatement>, ParseOfxError>;
+}
+
+struct RealOfxReader {}
+
+fn required<'a>(element: &'a Element, path: &str) -> Result<&'a str, ParseOfxError> {
+    element
+        .text(path)
+        .ok_or_else(|| ParseOfxError::MissingElement(path.to_owned()))
+}
+
+fn read_transaction(element: &Element) -> Result<OfxTransaction, ParseOfxError> {
+    let optional = |path: &str| element.text(path).map(|t| t.to_owned());
+
+    Ok(OfxTransaction {
+        trn_type: required(element, "TRNTYPE")?.to_owned(),
+        dt_posted: required(element, "DTPOSTED")?.to_owned(),
+        trn_amt: required(element, "TRNAMT")?.to_owned(),
+        fit_id: required(element, "FITID")?.to_owned(),
+        name: optional("NAME").or_else(|| optional("PAYEE/NAME")),
+        memo: optional("MEMO"),
+        check_num: optional("CHECKNUM"),
+    })
+}
+
+/// Reads a bank (`STMTRS`) or credit card (`CCSTMTRS`) statement response.
+fn read_statement(element: &Element) -> Result<OfxStatement, ParseOfxError> {
+    let acct_id = required(element, "BANKACCTFROM/ACCTID")
+        .or_else(|_| required(element, "CCACCTFROM/ACCTID"))?;
+
+    let transactions = match element.child("BANKTRANLIST") {
+        Some(list) => list
+            .children("STMTTRN")
+            .map(read_transaction)
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+
+    let ledger_bal = match element.child("LEDGERBAL") {
+        Some(balance) => Some(OfxBalance {
+            bal_amt: required(balance, "BALAMT")?.to_owned(),
+            dt_as_of: required(balance, "DTASOF")?.to_owned(),
+        }),
+        None => None,
+    };
+
+    Ok(OfxStatement {
+        acct_id: acct_id.to_owned(),
+        cur_def: required(element, "CURDEF")?.to_owned(),
+        transactions,
+        ledger_bal,
+    })
+}
+
+/// Collects the statement responses of every message set, e.g.
+/// `BANKMSGSRSV1/STMTTRNRS/STMTRS`.
+fn statement_elements<'a>(element: &'a Element, statements: &mut Vec<&'a Element>) {
+    for child in &element.children {
+        if child.name == "STMTRS" || child.name == "CCSTMTRS" {
+            statements.push(child);
+        } else {
+            statement_elements(child, statements);
+        }
+    }
+}
+
+impl OfxReader for RealOfxReader {
+    fn read_from_str(&self, ofx_contents: &str) -> Result<Vec<OfxStatement>, ParseOfxError> {
+        let document = markup::read_document(ofx_contents)?;
+
+        let mut elements = vec![];
+        statement_elements(&document, &mut elements);
+
+        elements.into_iter().map(read_statement).collect()
+    }
+}
+
+struct StubbedOfxReader {
+    statements: Vec<OfxStatement>,
+}
+
+impl OfxReader for StubbedOfxReader {
+    fn read_from_str(&self, _ofx_contents: &str) -> Result<Vec<OfxStatement>, ParseOfxError> {
+        Ok(self.statements.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(transactions: Vec<OfxTransaction>) -> OfxStatement {
+        OfxStatement {
+            acct_id: "1234567890".to_string(),
+            cur_def: "USD".to_string(),
+            transactions,
+            ledger_bal: Some(OfxBalance {
+                bal_amt: "-350.25".to_string(),
+                dt_as_of: "20230430".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_empty_ofx_parser() {
+        let ofx_parser = OfxParser::create_nullable(vec![]);
+
+        assert_eq!(
+            ofx_parser.parse_file("mocked").expect("File to be parsed"),
+            vec![]
+        )
+    }
+
+    #[test]
+    fn test_statements_have_entries() {
+        let ofx_parser = OfxParser::create_nullable(vec![statement(vec![
+            OfxTransaction::new(
+                "20230403120000[-5:EST]",
+                "-12.50",
+                "202304030001",
+                Some("COFFEE SHOP".to_string()),
+                Some("Card  1234".to_string()),
+            ),
+            OfxTransaction::new("20230405", "+.75", "202304050001", None, None),
+        ])]);
+
+        let entries = ofx_parser.parse_file("mocked").expect("File to be parsed");
+
+        let mut expected = Entry::new(
+            "1234567890".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 3).expect("date to be valid"),
+            Some("COFFEE SHOP".to_string()),
+            Some("Card 1234".to_string()),
+            None,
+            Some(Money::parse("12.50", "USD").expect("amount to be valid")),
+        );
+        expected.bank_transaction_code = Some("OTHER".to_string());
+        expected.reference = Some("202304030001".to_string());
+        expected.import_id = Some("202304030001".to_string());
+        assert_eq!(entries[0], expected);
+
+        assert_eq!(
+            entries[1].inflow,
+            Some(Money::parse("0.75", "USD").expect("amount to be valid"))
+        );
+    }
+
+    #[test]
+    fn test_statements_have_ledger_balance() {
+        let ofx_parser = OfxParser::create_nullable(vec![statement(vec![])]);
+
+        let statements = ofx_parser
+            .parse_statements("mocked")
+            .expect("File to be parsed");
+
+        assert_eq!(statements[0].opening_balance, None);
+        assert_eq!(
+            statements[0].closing_balance,
+            Some(Money::parse("-350.25", "USD").expect("amount to be valid"))
+        );
+    }
+
+    #[test]
+    fn test_transaction_with_invalid_date() {
+        let ofx_parser = OfxParser::create_nullable(vec![statement(vec![OfxTransaction::new(
+            "2023-04-03",
+            "1.00",
+            "1",
+            None,
+            None,
+        )])]);
+
+        let result = ofx_parser.parse_file("mocked");
+
+        assert!(matches!(
+            result,
+            Err(ParseOfxError::InvalidTransaction { fit_id, source })
+                if fit_id == "1" && matches!(*source, ParseOfxError::DateError(_))
+        ));
+    }
+
+    #[test]
+    fn test_transaction_with_invalid_amount() {
+        let ofx_parser = OfxParser::create_nullable(vec![statement(vec![OfxTransaction::new(
+            "20230403", "1.0O", "1", None, None,
+        )])]);
+
+        let error = ofx_parser
+            .parse_file("mocked")
+            .expect_err("File not to be parsed");
+
+        assert_eq!(
+            error.to_string(),
+            "Transaction 1: Invalid amount '1.0O' in <TRNAMT>: Invalid amount '1.0O'"
+        );
+    }
+}
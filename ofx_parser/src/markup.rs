@@ -0,0 +1,229 @@
+use crate::ParseOfxError;
+
+/// An OFX element. Aggregates have children, leaf elements have text.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Element {
+    pub name: String,
+    pub text: Option<String>,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// The text of the element at the `/` separated path, e.g. `LEDGERBAL/BALAMT`.
+    pub fn text(&self, path: &str) -> Option<&str> {
+        path.split('/')
+            .try_fold(self, |element, name| element.child(name))?
+            .text
+            .as_deref()
+    }
+}
+
+/// OFX elements that contain other elements. Any other element is a leaf,
+/// which SGML doesn't close with an end tag.
+const AGGREGATES: &[&str] = &[
+    "OFX",
+    "SIGNONMSGSRSV1",
+    "SONRS",
+    "STATUS",
+    "FI",
+    "BANKMSGSRSV1",
+    "STMTTRNRS",
+    "STMTRS",
+    "CREDITCARDMSGSRSV1",
+    "CCSTMTTRNRS",
+    "CCSTMTRS",
+    "BANKACCTFROM",
+    "BANKACCTTO",
+    "CCACCTFROM",
+    "CCACCTTO",
+    "BANKTRANLIST",
+    "STMTTRN",
+    "PAYEE",
+    "CURRENCY",
+    "ORIGCURRENCY",
+    "LEDGERBAL",
+    "AVAILBAL",
+    "BALLIST",
+    "BAL",
+];
+
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Closes the innermost open element and attaches it to its parent.
+fn close(stack: &mut Vec<Element>) {
+    if stack.len() > 1 {
+        let element = stack.pop().expect("stack to have an element");
+        stack
+            .last_mut()
+            .expect("stack to have a parent")
+            .children
+            .push(element);
+    }
+}
+
+/// Reads the `<OFX>` element of both OFX 1.x SGML, where leaf elements have
+/// no end tag, and OFX 2.x XML. Headers, processing instructions and
+/// comments are skipped.
+pub fn read_document(contents: &str) -> Result<Element, ParseOfxError> {
+    let start = contents
+        .as_bytes()
+        .windows(5)
+        .position(|w| w.eq_ignore_ascii_case(b"<OFX>"))
+        .ok_or_else(|| ParseOfxError::MissingElement("OFX".to_owned()))?;
+    // OFX 2.x declares itself with an XML or OFX processing instruction.
+    let sgml = !contents[..start].contains("<?");
+    let mut rest = &contents[start..];
+
+    let mut stack = vec![Element::default()];
+
+    while let Some(open) = rest.find('<') {
+        let text = rest[..open].trim();
+        if !text.is_empty() && stack.len() > 1 {
+            if let Some(element) = stack.last_mut() {
+                element.text = Some(decode(text));
+            }
+        }
+
+        rest = &rest[open..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|e| e + 3).unwrap_or(rest.len());
+            rest = &rest[end..];
+            continue;
+        }
+
+        let close_tag = rest
+            .find('>')
+            .ok_or_else(|| ParseOfxError::InvalidMarkup(rest.to_owned()))?;
+        let tag = &rest[1..close_tag];
+        rest = &rest[close_tag + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        // A leaf element ends at the next tag in SGML, even without text.
+        let open_leaf = |stack: &Vec<Element>| {
+            sgml && stack
+                .last()
+                .is_some_and(|e| stack.len() > 1 && !AGGREGATES.contains(&e.name.as_str()))
+        };
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_uppercase();
+            if !stack.iter().skip(1).any(|e| e.name == name) {
+                return Err(ParseOfxError::InvalidMarkup(format!("</{}>", name)));
+            }
+            while stack.last().is_some_and(|e| e.name != name) {
+                close(&mut stack);
+            }
+            close(&mut stack);
+        } else {
+            if open_leaf(&stack) {
+                close(&mut stack);
+            }
+            let self_closing = tag.ends_with('/');
+            stack.push(Element {
+                name: tag.trim_end_matches('/').trim().to_uppercase(),
+                ..Element::default()
+            });
+            if self_closing {
+                close(&mut stack);
+            }
+        }
+    }
+
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+
+    stack
+        .pop()
+        .and_then(|root| root.children.into_iter().find(|c| c.name == "OFX"))
+        .ok_or_else(|| ParseOfxError::MissingElement("OFX".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(name: &str, text: &str) -> Element {
+        Element {
+            name: name.to_string(),
+            text: Some(text.to_string()),
+            children: vec![],
+        }
+    }
+
+    fn aggregate(name: &str, children: Vec<Element>) -> Element {
+        Element {
+            name: name.to_string(),
+            text: None,
+            children,
+        }
+    }
+
+    #[test]
+    fn reads_sgml_and_xml() {
+        let sgml = "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX>\n<STMTTRN>\n<TRNAMT>-12.50\n<NAME>Tom &amp; Jerry\n</STMTTRN>\n<LEDGERBAL><BALAMT>100</LEDGERBAL>\n</OFX>";
+        let xml = "<?xml version=\"1.0\"?>\n<?OFX OFXHEADER=\"200\"?>\n<OFX><!-- comment --><STMTTRN><TRNAMT>-12.50</TRNAMT><NAME>Tom &amp; Jerry</NAME></STMTTRN><LEDGERBAL><BALAMT>100</BALAMT></LEDGERBAL></OFX>";
+
+        let expected = aggregate(
+            "OFX",
+            vec![
+                aggregate(
+                    "STMTTRN",
+                    vec![leaf("TRNAMT", "-12.50"), leaf("NAME", "Tom & Jerry")],
+                ),
+                aggregate("LEDGERBAL", vec![leaf("BALAMT", "100")]),
+            ],
+        );
+
+        assert_eq!(read_document(sgml).expect("sgml to be read"), expected);
+        assert_eq!(read_document(xml).expect("xml to be read"), expected);
+        assert_eq!(expected.text("LEDGERBAL/BALAMT"), Some("100"));
+    }
+
+    #[test]
+    fn closes_sgml_leaf_without_text() {
+        let sgml = "<OFX>\n<STMTTRN>\n<MEMO>\n<TRNAMT>-12.50\n<NAME>\n</STMTTRN>\n</OFX>";
+
+        let document = read_document(sgml).expect("sgml to be read");
+
+        assert_eq!(
+            document,
+            aggregate(
+                "OFX",
+                vec![aggregate(
+                    "STMTTRN",
+                    vec![
+                        aggregate("MEMO", vec![]),
+                        leaf("TRNAMT", "-12.50"),
+                        aggregate("NAME", vec![]),
+                    ],
+                )],
+            )
+        );
+    }
+
+    #[test]
+    fn fails_on_unmatched_end_tag() {
+        let result = read_document("<OFX><STMTTRN></BANKTRANLIST></OFX>");
+
+        assert!(matches!(result, Err(ParseOfxError::InvalidMarkup(_))));
+    }
+}
@@ -0,0 +1,49 @@
+/// A `STMTTRN` aggregate.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OfxTransaction {
+    pub trn_type: String,
+    /// `YYYYMMDD`, optionally followed by a time and time zone.
+    pub dt_posted: String,
+    /// Signed amount, e.g. `-12.50`.
+    pub trn_amt: String,
+    pub fit_id: String,
+    pub name: Option<String>,
+    pub memo: Option<String>,
+    pub check_num: Option<String>,
+}
+
+impl OfxTransaction {
+    pub fn new(
+        dt_posted: &str,
+        trn_amt: &str,
+        fit_id: &str,
+        name: Option<String>,
+        memo: Option<String>,
+    ) -> Self {
+        OfxTransaction {
+            trn_type: "OTHER".to_string(),
+            dt_posted: dt_posted.to_string(),
+            trn_amt: trn_amt.to_string(),
+            fit_id: fit_id.to_string(),
+            name,
+            memo,
+            check_num: None,
+        }
+    }
+}
+
+/// A `LEDGERBAL` aggregate.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OfxBalance {
+    pub bal_amt: String,
+    pub dt_as_of: String,
+}
+
+/// A bank (`STMTRS`) or credit card (`CCSTMTRS`) statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OfxStatement {
+    pub acct_id: String,
+    pub cur_def: String,
+    pub transactions: Vec<OfxTransaction>,
+    pub ledger_bal: Option<OfxBalance>,
+}
@@ -0,0 +1,75 @@
+use std::fs;
+
+use chrono::NaiveDate;
+use common::{money::Money, parser::StatementParser, Entry};
+use ofx_parser::OfxParser;
+
+#[test]
+fn sgml() {
+    let ofx_parser = OfxParser::create();
+    let file_contents = fs::read_to_string("resources/example.qfx").expect("File to be read");
+
+    let mut result = ofx_parser
+        .parse_file(&file_contents)
+        .expect("qfx file to be parsed");
+
+    let mut expected = Entry::new(
+        "4111111111111111".to_string(),
+        NaiveDate::from_ymd_opt(2023, 4, 3).expect("date to be valid"),
+        Some("BLUE BOTTLE COFFEE".to_string()),
+        Some("OAKLAND CA".to_string()),
+        None,
+        Some(Money::parse("12.50", "USD").expect("amount to be valid")),
+    );
+    expected.bank_transaction_code = Some("DEBIT".to_string());
+    expected.reference = Some("2023040324692163".to_string());
+    expected.import_id = Some("2023040324692163".to_string());
+    assert_eq!(result.remove(0), expected);
+
+    let entry = result.remove(0);
+    assert_eq!(entry.payee, Some("PAYMENT - THANK YOU".to_string()));
+    assert_eq!(
+        entry.inflow,
+        Some(Money::parse("250.00", "USD").expect("amount to be valid"))
+    );
+
+    let entry = result.remove(0);
+    assert_eq!(entry.payee, Some("JOE & SONS HARDWARE".to_string()));
+    assert!(result.is_empty());
+}
+
+#[test]
+fn xml() {
+    let ofx_parser = OfxParser::create();
+    let file_contents = fs::read_to_string("resources/example.ofx").expect("File to be read");
+
+    let statements = ofx_parser
+        .parse_statements(&file_contents)
+        .expect("ofx file to be parsed");
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].account, "0123456789");
+    assert_eq!(
+        statements[0].closing_balance,
+        Some(Money::parse("2700.00", "USD").expect("amount to be valid"))
+    );
+
+    let entry = &statements[0].entries[1];
+    assert_eq!(entry.payee, Some("PROPERTY MANAGEMENT LLC".to_string()));
+    assert_eq!(entry.import_id, Some("CHK20230410".to_string()));
+    assert_eq!(
+        entry.outflow,
+        Some(Money::parse("800.00", "USD").expect("amount to be valid"))
+    );
+}
+
+#[test]
+fn detects_format() {
+    let ofx_parser = OfxParser::create();
+
+    for path in ["resources/example.qfx", "resources/example.ofx"] {
+        let file_contents = fs::read(path).expect("File to be read");
+        assert!(ofx_parser.detect(&file_contents));
+    }
+    assert!(!ofx_parser.detect(b":20:1\n:25:NL81ASNB9999999999\n"));
+}
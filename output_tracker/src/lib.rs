@@ -31,6 +31,15 @@ where
     }
 }
 
+impl<T> Default for OutputTracker<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct OutputListener<T> {
     listeners: Vec<Rc<OutputTracker<T>>>,
 }
@@ -57,6 +66,15 @@ where
     }
 }
 
+impl<T> Default for OutputListener<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
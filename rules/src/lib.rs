@@ -0,0 +1,552 @@
+pub mod model;
+use regex::{Captures, Regex};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::model::{Actions, Direction, RuleConfig};
+use common::{normalize_account, Entry};
+
+#[derive(Error, Debug)]
+pub enum RuleError {
+    #[error("Rule '{rule}' has an invalid {field} regex")]
+    InvalidRegex {
+        rule: String,
+        field: &'static str,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("Rule '{rule}' has an amount range with min {min} greater than max {max}")]
+    InvalidAmountRange {
+        rule: String,
+        min: Decimal,
+        max: Decimal,
+    },
+    #[error("Rule '{rule}' has no conditions")]
+    NoConditions { rule: String },
+    #[error("Rule '{rule}' has no actions")]
+    NoActions { rule: String },
+}
+
+#[derive(Debug)]
+enum Condition {
+    Payee(Regex),
+    Memo(Regex),
+    Text(Regex),
+    Amount {
+        min: Option<Decimal>,
+        max: Option<Decimal>,
+    },
+    Direction(Direction),
+    Account(String),
+    CounterpartyAccount(String),
+    BankTransactionCode(String),
+}
+
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    conditions: Vec<Condition>,
+    actions: Actions,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct RuleOutcome {
+    pub fired: Vec<String>,
+    pub skip: bool,
+}
+
+pub struct RulesEngine {
+    rules: Vec<Rule>,
+}
+
+impl RulesEngine {
+    pub fn new(rule_configs: Vec<RuleConfig>) -> Result<Self, RuleError> {
+        let rules = rule_configs
+            .into_iter()
+            .enumerate()
+            .map(|(index, config)| Rule::try_from_config(index, config))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RulesEngine { rules })
+    }
+
+    /// Applies every matching rule in order. A rule that skips the entry or
+    /// is marked `stop` ends processing for that entry.
+    pub fn apply(&self, entry: &mut Entry) -> RuleOutcome {
+        let mut outcome = RuleOutcome::default();
+
+        for rule in self.rules.iter() {
+            if !rule.apply(entry) {
+                continue;
+            }
+
+            outcome.fired.push(rule.name.to_owned());
+
+            if rule.actions.skip {
+                outcome.skip = true;
+                break;
+            }
+            if rule.actions.stop {
+                break;
+            }
+        }
+
+        outcome
+    }
+}
+
+impl Rule {
+    fn try_from_config(index: usize, config: RuleConfig) -> Result<Self, RuleError> {
+        let name = config.name.unwrap_or_else(|| format!("rule {}", index + 1));
+        let when = config.when;
+
+        let regex = |field: &'static str, pattern: &str| {
+            Regex::new(pattern).map_err(|source| RuleError::InvalidRegex {
+                rule: name.to_owned(),
+                field,
+                source,
+            })
+        };
+
+        let mut conditions = vec![];
+        if let Some(pattern) = when.payee {
+            conditions.push(Condition::Payee(regex("payee", &pattern)?));
+        }
+        if let Some(pattern) = when.memo {
+            conditions.push(Condition::Memo(regex("memo", &pattern)?));
+        }
+        if let Some(pattern) = when.text {
+            conditions.push(Condition::Text(regex("text", &pattern)?));
+        }
+        if let Some(amount) = when.amount {
+            if let (Some(min), Some(max)) = (amount.min, amount.max) {
+                if min > max {
+                    return Err(RuleError::InvalidAmountRange {
+                        rule: name,
+                        min,
+                        max,
+                    });
+                }
+            }
+            conditions.push(Condition::Amount {
+                min: amount.min,
+                max: amount.max,
+            });
+        }
+        if let Some(direction) = when.direction {
+            conditions.push(Condition::Direction(direction));
+        }
+        if let Some(account) = when.account {
+            conditions.push(Condition::Account(normalize_account(&account)));
+        }
+        if let Some(account) = when.counterparty_account {
+            conditions.push(Condition::CounterpartyAccount(normalize_account(&account)));
+        }
+        if let Some(code) = when.bank_transaction_code {
+            conditions.push(Condition::BankTransactionCode(code));
+        }
+
+        if conditions.is_empty() {
+            return Err(RuleError::NoConditions { rule: name });
+        }
+
+        let actions = config.then;
+        if actions.payee.is_none()
+            && actions.memo.is_none()
+            && actions.category.is_none()
+            && actions.flag.is_none()
+            && !actions.skip
+        {
+            return Err(RuleError::NoActions { rule: name });
+        }
+
+        Ok(Rule {
+            name,
+            conditions,
+            actions,
+        })
+    }
+
+    /// Returns whether the rule matched, in which case its actions have been
+    /// applied to the entry.
+    fn apply(&self, entry: &mut Entry) -> bool {
+        let mut payee_captures: Option<Captures> = None;
+        let mut memo_captures: Option<Captures> = None;
+
+        for condition in self.conditions.iter() {
+            let matched = match condition {
+                Condition::Payee(regex) => capture(regex, &entry.payee, &mut payee_captures),
+                Condition::Memo(regex) => capture(regex, &entry.memo, &mut memo_captures),
+                Condition::Text(regex) => {
+                    capture(regex, &entry.payee, &mut payee_captures)
+                        || capture(regex, &entry.memo, &mut memo_captures)
+                }
+                Condition::Amount { min, max } => amount(entry).is_some_and(|amount| {
+                    min.is_none_or(|min| amount >= min) && max.is_none_or(|max| amount <= max)
+                }),
+                Condition::Direction(Direction::Inflow) => entry.inflow.is_some(),
+                Condition::Direction(Direction::Outflow) => entry.outflow.is_some(),
+                Condition::Account(account) => normalize_account(&entry.account).eq(account),
+                Condition::CounterpartyAccount(account) => entry
+                    .counterparty_account
+                    .as_deref()
+                    .is_some_and(|a| normalize_account(a).eq(account)),
+                Condition::BankTransactionCode(code) => entry
+                    .bank_transaction_code
+                    .as_ref()
+                    .is_some_and(|c| c.eq(code)),
+            };
+
+            if !matched {
+                return false;
+            }
+        }
+
+        let payee =
+            self.actions.payee.as_ref().map(|template| {
+                expand(template, payee_captures.as_ref().or(memo_captures.as_ref()))
+            });
+        let memo =
+            self.actions.memo.as_ref().map(|template| {
+                expand(template, memo_captures.as_ref().or(payee_captures.as_ref()))
+            });
+
+        if payee.is_some() {
+            entry.payee = payee;
+        }
+        if memo.is_some() {
+            entry.memo = memo;
+        }
+        if let Some(category) = self.actions.category.as_ref() {
+            entry.category = Some(category.to_owned());
+        }
+        if let Some(flag) = self.actions.flag {
+            entry.flag = Some(flag);
+        }
+
+        true
+    }
+}
+
+fn capture<'h>(
+    regex: &Regex,
+    field: &'h Option<String>,
+    captures: &mut Option<Captures<'h>>,
+) -> bool {
+    match field.as_deref().and_then(|f| regex.captures(f)) {
+        Some(c) => {
+            *captures = Some(c);
+            true
+        }
+        None => false,
+    }
+}
+
+fn expand(template: &str, captures: Option<&Captures>) -> String {
+    match captures {
+        Some(captures) => {
+            let mut result = String::new();
+            captures.expand(template, &mut result);
+            result
+        }
+        None => template.to_owned(),
+    }
+}
+
+fn amount(entry: &Entry) -> Option<Decimal> {
+    entry.inflow.or(entry.outflow).map(|m| m.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::*;
+    use chrono::NaiveDate;
+    use common::{money::Money, FlagColor};
+
+    use super::*;
+
+    fn entry() -> Entry {
+        let mut entry = Entry::new(
+            "NL00BANK0000000000".to_string(),
+            NaiveDate::from_ymd_opt(2023, 4, 27).expect("date to be valid"),
+            Some("CCV*CAFETARIA SIM".to_string()),
+            Some("GRONINGEN27.04.2023 18U57 KV005 CT371516".to_string()),
+            None,
+            Some(Money::parse("19.3", "EUR").expect("amount to be valid")),
+        );
+        entry.bank_transaction_code = Some("7913".to_string());
+        entry.counterparty_account = Some("NL61COBA0733974775".to_string());
+        entry
+    }
+
+    fn rule(when: Conditions, then: Actions) -> RuleConfig {
+        RuleConfig {
+            name: None,
+            when,
+            then,
+        }
+    }
+
+    fn set_category(category: &str) -> Actions {
+        Actions {
+            category: Some(category.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rule_sets_payee_category_and_flag() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                payee: Some("^CCV\\*CAFETARIA".to_string()),
+                ..Default::default()
+            },
+            Actions {
+                payee: Some("Cafetaria Sim".to_string()),
+                category: Some("Eating out".to_string()),
+                flag: Some(FlagColor::Red),
+                ..Default::default()
+            },
+        )])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        let outcome = engine.apply(&mut entry);
+
+        assert_eq!(
+            outcome,
+            RuleOutcome {
+                fired: vec!["rule 1".to_string()],
+                skip: false
+            }
+        );
+        assert_eq!(entry.payee, Some("Cafetaria Sim".to_string()));
+        assert_eq!(entry.category, Some("Eating out".to_string()));
+        assert_eq!(entry.flag, Some(FlagColor::Red));
+    }
+
+    #[test]
+    fn memo_rewrite_uses_capture_groups() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                memo: Some("^(?<city>[A-Z]+)(?<date>\\d{2}\\.\\d{2}\\.\\d{4})".to_string()),
+                ..Default::default()
+            },
+            Actions {
+                memo: Some("Card payment ${date} in ${city}".to_string()),
+                ..Default::default()
+            },
+        )])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        engine.apply(&mut entry);
+
+        assert_eq!(
+            entry.memo,
+            Some("Card payment 27.04.2023 in GRONINGEN".to_string())
+        );
+    }
+
+    #[test]
+    fn all_conditions_must_match() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                payee: Some("CAFETARIA".to_string()),
+                direction: Some(Direction::Inflow),
+                ..Default::default()
+            },
+            set_category("Income"),
+        )])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        let outcome = engine.apply(&mut entry);
+
+        assert_eq!(outcome, RuleOutcome::default());
+        assert_eq!(entry.category, None);
+    }
+
+    #[test]
+    fn matches_amount_account_counterparty_and_code() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                amount: Some(AmountRange {
+                    min: Some(Decimal::TEN),
+                    max: Some(Decimal::new(193, 1)),
+                }),
+                direction: Some(Direction::Outflow),
+                account: Some("nl00 bank 0000 0000 00".to_string()),
+                counterparty_account: Some("NL61COBA0733974775".to_string()),
+                bank_transaction_code: Some("7913".to_string()),
+                ..Default::default()
+            },
+            set_category("Eating out"),
+        )])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        engine.apply(&mut entry);
+
+        assert_eq!(entry.category, Some("Eating out".to_string()));
+    }
+
+    #[test]
+    fn amount_outside_range_does_not_match() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                amount: Some(AmountRange {
+                    min: None,
+                    max: Some(Decimal::TEN),
+                }),
+                ..Default::default()
+            },
+            set_category("Small"),
+        )])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        engine.apply(&mut entry);
+
+        assert_eq!(entry.category, None);
+    }
+
+    #[test]
+    fn rules_apply_in_order_until_stop() {
+        let text = || Conditions {
+            text: Some("CAFETARIA".to_string()),
+            ..Default::default()
+        };
+        let engine = RulesEngine::new(vec![
+            rule(text(), set_category("First")),
+            RuleConfig {
+                name: Some("second".to_string()),
+                when: text(),
+                then: Actions {
+                    category: Some("Second".to_string()),
+                    stop: true,
+                    ..Default::default()
+                },
+            },
+            rule(text(), set_category("Third")),
+        ])
+        .expect("rules to be valid");
+        let mut entry = entry();
+
+        let outcome = engine.apply(&mut entry);
+
+        assert_eq!(
+            outcome.fired,
+            vec!["rule 1".to_string(), "second".to_string()]
+        );
+        assert_eq!(entry.category, Some("Second".to_string()));
+    }
+
+    #[test]
+    fn skip_marks_entry_for_removal() {
+        let engine = RulesEngine::new(vec![rule(
+            Conditions {
+                bank_transaction_code: Some("7913".to_string()),
+                ..Default::default()
+            },
+            Actions {
+                skip: true,
+                ..Default::default()
+            },
+        )])
+        .expect("rules to be valid");
+
+        assert!(engine.apply(&mut entry()).skip);
+    }
+
+    #[test]
+    fn legacy_payee_regex_matches_memo() {
+        let engine = RulesEngine::new(vec![RuleConfig::payee_regex("Groningen", "GRONINGEN")])
+            .expect("rules to be valid");
+        let mut entry = entry();
+
+        let outcome = engine.apply(&mut entry);
+
+        assert_eq!(entry.payee, Some("Groningen".to_string()));
+        assert_eq!(outcome.fired, vec!["payee_regex Groningen".to_string()]);
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        let result = RulesEngine::new(vec![rule(
+            Conditions {
+                memo: Some("(".to_string()),
+                ..Default::default()
+            },
+            set_category("Broken"),
+        )]);
+
+        assert!(matches!(
+            result,
+            Err(RuleError::InvalidRegex { field: "memo", .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_amount_range_is_rejected() {
+        let result = RulesEngine::new(vec![rule(
+            Conditions {
+                amount: Some(AmountRange {
+                    min: Some(Decimal::TWO),
+                    max: Some(Decimal::ONE),
+                }),
+                ..Default::default()
+            },
+            set_category("Broken"),
+        )]);
+
+        assert!(matches!(result, Err(RuleError::InvalidAmountRange { .. })));
+    }
+
+    #[test]
+    fn rule_without_conditions_or_actions_is_rejected() {
+        let result = RulesEngine::new(vec![rule(Conditions::default(), set_category("All"))]);
+        assert!(matches!(result, Err(RuleError::NoConditions { .. })));
+
+        let result = RulesEngine::new(vec![rule(
+            Conditions {
+                payee: Some("CAFETARIA".to_string()),
+                ..Default::default()
+            },
+            Actions::default(),
+        )]);
+        assert!(matches!(result, Err(RuleError::NoActions { .. })));
+    }
+
+    #[test]
+    fn rules_deserialize_from_yaml() {
+        let yaml = r#"
+- name: Cafetaria
+  when:
+    payee: "CAFETARIA"
+    amount: { min: 5, max: 19.3 }
+    direction: outflow
+  then:
+    payee: Cafetaria Sim
+    flag: purple
+- when:
+    bank_transaction_code: "6607"
+  then:
+    skip: true
+"#;
+
+        let rules: Vec<RuleConfig> = serde_yaml::from_str(yaml).expect("yaml to be parsed");
+
+        assert_eq!(
+            rules[0].when.amount,
+            Some(AmountRange {
+                min: Some(Decimal::new(5, 0)),
+                max: Some(Decimal::new(193, 1)),
+            })
+        );
+        assert_eq!(rules[0].when.direction, Some(Direction::Outflow));
+        assert_eq!(rules[0].then.flag, Some(FlagColor::Purple));
+        assert!(rules[1].then.skip);
+        assert!(RulesEngine::new(rules).is_ok());
+    }
+}
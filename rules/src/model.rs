@@ -0,0 +1,71 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use common::FlagColor;
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Inflow,
+    Outflow,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AmountRange {
+    pub min: Option<Decimal>,
+    pub max: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Conditions {
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub text: Option<String>,
+    pub amount: Option<AmountRange>,
+    pub direction: Option<Direction>,
+    pub account: Option<String>,
+    pub counterparty_account: Option<String>,
+    pub bank_transaction_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Actions {
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub category: Option<String>,
+    pub flag: Option<FlagColor>,
+    #[serde(default)]
+    pub skip: bool,
+    #[serde(default)]
+    pub stop: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    pub name: Option<String>,
+    pub when: Conditions,
+    pub then: Actions,
+}
+
+impl RuleConfig {
+    /// The equivalent of a legacy `payee_regex` entry: match the pattern on
+    /// payee or memo, set the payee and stop.
+    pub fn payee_regex(payee: &str, pattern: &str) -> Self {
+        RuleConfig {
+            name: Some(format!("payee_regex {}", payee)),
+            when: Conditions {
+                text: Some(pattern.to_owned()),
+                ..Default::default()
+            },
+            then: Actions {
+                payee: Some(payee.to_owned()),
+                stop: true,
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+#[derive(ValueEnum, Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    /// 2023-04-05
+    #[default]
+    Iso,
+    /// 05-04-2023
+    Dmy,
+    /// 04/05/2023
+    Mdy,
+}
+
+impl DateFormat {
+    pub fn format(&self, date: &NaiveDate) -> String {
+        let format = match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::Dmy => "%d-%m-%Y",
+            DateFormat::Mdy => "%m/%d/%Y",
+        };
+
+        date.format(format).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_dates() {
+        let date = NaiveDate::from_ymd_opt(2023, 4, 5).expect("date to be valid");
+
+        assert_eq!(DateFormat::Iso.format(&date), "2023-04-05");
+        assert_eq!(DateFormat::Dmy.format(&date), "05-04-2023");
+        assert_eq!(DateFormat::Mdy.format(&date), "04/05/2023");
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use common::Entry;
+
+/// Longest import ID YNAB accepts.
+const MAX_IMPORT_ID_LENGTH: usize = 36;
+
+/// Where import ids come from.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ImportIdMode {
+    /// Generated from the amount, date and occurrence, like YNAB does.
+    Ynab,
+    /// The bank's entry reference, when the statement provides one.
+    BankReference,
+}
+
+pub struct ImportIdGenerator {
+    mode: ImportIdMode,
+}
+
+impl ImportIdGenerator {
+    pub fn new(mode: ImportIdMode) -> Self {
+        ImportIdGenerator { mode }
+    }
+
+    /// Assigns `YNAB:<milliunits>:<date>:<occurrence>` import ids, counting
+    /// occurrences of the same amount and date per account in statement
+    /// order. In bank reference mode the bank's entry reference is used
+    /// instead whenever the statement provides one YNAB accepts. Import ids
+    /// that the statement itself provides, such as OFX FITIDs, are kept when
+    /// YNAB accepts them.
+    pub fn generate(&self, entries: &mut [Entry]) {
+        let mut occurrences: HashMap<(String, NaiveDate, i64), u32> = HashMap::new();
+
+        for entry in entries.iter_mut() {
+            match entry.import_id.as_ref() {
+                Some(id) if id.len() <= MAX_IMPORT_ID_LENGTH => continue,
+                _ => entry.import_id = None,
+            }
+
+            if self.mode == ImportIdMode::BankReference {
+                if let Some(reference) = entry
+                    .reference
+                    .as_ref()
+                    .filter(|r| r.len() <= MAX_IMPORT_ID_LENGTH)
+                {
+                    entry.import_id = Some(reference.to_owned());
+                    continue;
+                }
+            }
+
+            let Some(milliunits) = milliunits(entry) else {
+                continue;
+            };
+
+            let occurrence = occurrences
+                .entry((entry.account.to_owned(), entry.date, milliunits))
+                .or_insert(0);
+            *occurrence += 1;
+
+            entry.import_id = Some(format!("YNAB:{}:{}:{}", milliunits, entry.date, occurrence));
+        }
+    }
+}
+
+fn milliunits(entry: &Entry) -> Option<i64> {
+    match (entry.inflow.as_ref(), entry.outflow.as_ref()) {
+        (Some(inflow), _) => inflow.milliunits(),
+        (None, Some(outflow)) => outflow.milliunits().map(|m| -m),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::money::Money;
+
+    use super::*;
+
+    fn entry(account: &str, date: &str, inflow: Option<&str>, outflow: Option<&str>) -> Entry {
+        Entry::new(
+            account.to_string(),
+            date.parse().expect("date to be valid"),
+            None,
+            None,
+            inflow.map(|i| Money::parse(i, "EUR").expect("amount to be valid")),
+            outflow.map(|o| Money::parse(o, "EUR").expect("amount to be valid")),
+        )
+    }
+
+    fn import_ids(entries: &[Entry]) -> Vec<Option<String>> {
+        entries.iter().map(|e| e.import_id.to_owned()).collect()
+    }
+
+    #[test]
+    fn generates_ynab_import_ids() {
+        let mut entries = vec![
+            entry("NL00", "2023-04-27", None, Some("19.3")),
+            entry("NL00", "2023-04-28", Some("1200"), None),
+            entry("NL00", "2023-04-28", None, Some("0.05")),
+        ];
+
+        ImportIdGenerator::new(ImportIdMode::Ynab).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![
+                Some("YNAB:-19300:2023-04-27:1".to_string()),
+                Some("YNAB:1200000:2023-04-28:1".to_string()),
+                Some("YNAB:-50:2023-04-28:1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_occurrences_per_account_and_date() {
+        let mut entries = vec![
+            entry("NL00", "2023-04-27", None, Some("5")),
+            entry("NL86", "2023-04-27", None, Some("5")),
+            entry("NL00", "2023-04-27", None, Some("5")),
+            entry("NL00", "2023-04-28", None, Some("5")),
+        ];
+
+        ImportIdGenerator::new(ImportIdMode::Ynab).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![
+                Some("YNAB:-5000:2023-04-27:1".to_string()),
+                Some("YNAB:-5000:2023-04-27:1".to_string()),
+                Some("YNAB:-5000:2023-04-27:2".to_string()),
+                Some("YNAB:-5000:2023-04-28:1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bank_reference_mode_prefers_reference() {
+        let mut with_reference = entry("NL00", "2023-04-28", None, Some("10"));
+        with_reference.reference = Some("20230428-51415098".to_string());
+        let mut entries = vec![
+            with_reference,
+            entry("NL00", "2023-04-28", None, Some("10")),
+        ];
+
+        ImportIdGenerator::new(ImportIdMode::BankReference).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![
+                Some("20230428-51415098".to_string()),
+                Some("YNAB:-10000:2023-04-28:1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rounds_sub_milliunit_amounts() {
+        let mut entries = vec![entry("NL00", "2023-04-28", Some("0.0125"), None)];
+
+        ImportIdGenerator::new(ImportIdMode::Ynab).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![Some("YNAB:12:2023-04-28:1".to_string())]
+        );
+    }
+
+    #[test]
+    fn bank_reference_mode_skips_long_reference() {
+        let mut with_long_reference = entry("NL00", "2023-04-28", None, Some("10"));
+        with_long_reference.reference = Some("R".repeat(37));
+        let mut entries = vec![with_long_reference];
+
+        ImportIdGenerator::new(ImportIdMode::BankReference).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![Some("YNAB:-10000:2023-04-28:1".to_string())]
+        );
+    }
+
+    #[test]
+    fn keeps_statement_import_ids() {
+        let mut with_fitid = entry("NL00", "2023-04-28", None, Some("10"));
+        with_fitid.import_id = Some("202304280001".to_string());
+        let mut with_long_fitid = entry("NL00", "2023-04-28", None, Some("10"));
+        with_long_fitid.import_id = Some("0".repeat(37));
+        let mut entries = vec![with_fitid, with_long_fitid];
+
+        ImportIdGenerator::new(ImportIdMode::Ynab).generate(&mut entries);
+
+        assert_eq!(
+            import_ids(&entries),
+            vec![
+                Some("202304280001".to_string()),
+                Some("YNAB:-10000:2023-04-28:1".to_string()),
+            ]
+        );
+    }
+}
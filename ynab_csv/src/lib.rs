@@ -1,13 +1,13 @@
-use std::{
-    io::{Error, ErrorKind},
-    rc::Rc,
-};
+pub mod date_format;
+pub mod import_id;
+use std::{io::Error, rc::Rc};
 
 use thiserror::Error;
 
 use serde::Serialize;
 
-use common::Entry;
+use common::{ClearedState, Entry, FlagColor};
+use date_format::DateFormat;
 use output_tracker::{OutputListener, OutputTracker};
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
@@ -15,38 +15,60 @@ use output_tracker::{OutputListener, OutputTracker};
 pub struct YnabCsv {
     date: String,
     payee: Option<String>,
+    category: Option<String>,
     memo: Option<String>,
     outflow: Option<String>,
     inflow: Option<String>,
+    flag: Option<String>,
+    cleared: String,
+    import_id: Option<String>,
 }
 
 impl YnabCsv {
     pub fn new(
         date: String,
         payee: Option<String>,
+        category: Option<String>,
         memo: Option<String>,
         outflow: Option<String>,
         inflow: Option<String>,
+        flag: Option<FlagColor>,
     ) -> YnabCsv {
         YnabCsv {
             date,
             payee,
+            category,
             memo,
             outflow,
             inflow,
+            flag: flag.map(|f| f.to_string()),
+            cleared: ClearedState::Cleared.to_string(),
+            import_id: None,
         }
     }
-}
 
-impl From<Entry> for YnabCsv {
-    fn from(value: Entry) -> Self {
+    pub fn with_cleared(mut self, cleared: ClearedState) -> Self {
+        self.cleared = cleared.to_string();
+        self
+    }
+
+    pub fn with_import_id(mut self, import_id: Option<String>) -> Self {
+        self.import_id = import_id;
+        self
+    }
+
+    pub fn from_entry(value: Entry, date_format: &DateFormat) -> Self {
         YnabCsv::new(
-            value.date,
+            date_format.format(&value.date),
             value.payee,
+            value.category,
             value.memo,
-            value.outflow,
-            value.inflow,
+            value.outflow.map(|o| o.to_string()),
+            value.inflow.map(|i| i.to_string()),
+            value.flag,
         )
+        .with_cleared(value.cleared)
+        .with_import_id(value.import_id)
     }
 }
 
@@ -63,20 +85,23 @@ pub enum SerializeStatementsError {
 pub struct YnabCsvSerializer {
     output_listener: OutputListener<Vec<YnabCsv>>,
     csv_serializer: Box<dyn CsvSerializer>,
+    date_format: DateFormat,
 }
 
-impl<'a> YnabCsvSerializer {
-    pub fn create_nullable() -> YnabCsvSerializer {
+impl YnabCsvSerializer {
+    pub fn create_nullable(date_format: DateFormat) -> YnabCsvSerializer {
         YnabCsvSerializer {
             output_listener: OutputListener::new(),
             csv_serializer: Box::new(StubbedCsvSerializer {}),
+            date_format,
         }
     }
 
-    pub fn create() -> YnabCsvSerializer {
+    pub fn create(date_format: DateFormat) -> YnabCsvSerializer {
         YnabCsvSerializer {
             output_listener: OutputListener::new(),
             csv_serializer: Box::new(RealCsvSerializer {}),
+            date_format,
         }
     }
 
@@ -87,7 +112,7 @@ impl<'a> YnabCsvSerializer {
     pub fn serialize(&self, entries: Vec<Entry>) -> Result<String, SerializeStatementsError> {
         let mut ynab_csv: Vec<YnabCsv> = vec![];
         for stmt in entries {
-            ynab_csv.push(stmt.into());
+            ynab_csv.push(YnabCsv::from_entry(stmt, &self.date_format));
         }
 
         self.output_listener.track(&ynab_csv);
@@ -114,7 +139,7 @@ impl CsvSerializer for RealCsvSerializer {
 
         let result = wtr
             .into_inner()
-            .map_err(|_| Error::new(ErrorKind::Other, "Into Inner error"))?;
+            .map_err(|_| Error::other("Into Inner error"))?;
 
         let result = String::from_utf8(result)?;
 
@@ -126,29 +151,39 @@ impl CsvSerializer for RealCsvSerializer {
 struct StubbedCsvSerializer {}
 
 impl CsvSerializer for StubbedCsvSerializer {
-    fn serialize(&self, entries: Vec<YnabCsv>) -> Result<String, SerializeStatementsError> {
+    fn serialize(&self, _entries: Vec<YnabCsv>) -> Result<String, SerializeStatementsError> {
         Ok("asdf".to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::NaiveDate;
+    use common::money::Money;
+
     use super::*;
 
     #[test]
     fn serialize_statements_test() {
-        let mut ynab_csv_serializer = YnabCsvSerializer::create_nullable();
+        let mut ynab_csv_serializer = YnabCsvSerializer::create_nullable(DateFormat::Dmy);
         let tracker = ynab_csv_serializer.track_output();
 
         let result = ynab_csv_serializer
-            .serialize(vec![Entry::new(
-                "Account".to_string(),
-                "17-12-1999".to_string(),
-                Some("Albert Heijn".to_string()),
-                Some("Memo".to_string()),
-                Some("120".to_string()),
-                None,
-            )])
+            .serialize(vec![{
+                let mut entry = Entry::new(
+                    "Account".to_string(),
+                    NaiveDate::from_ymd_opt(1999, 12, 17).expect("date to be valid"),
+                    Some("Albert Heijn".to_string()),
+                    Some("Memo".to_string()),
+                    Some(Money::parse("120", "EUR").expect("amount to be valid")),
+                    None,
+                );
+                entry.category = Some("Groceries".to_string());
+                entry.flag = Some(FlagColor::Green);
+                entry.import_id = Some("YNAB:120000:1999-12-17:1".to_string());
+                entry.cleared = ClearedState::Uncleared;
+                entry
+            }])
             .expect("stmt to be serialized");
 
         let mut output = tracker.flush();
@@ -158,10 +193,14 @@ mod tests {
             vec![YnabCsv::new(
                 "17-12-1999".to_string(),
                 Some("Albert Heijn".to_string()),
+                Some("Groceries".to_string()),
                 Some("Memo".to_string()),
                 None,
-                Some("120".to_string()),
-            )]
+                Some("120.00".to_string()),
+                Some(FlagColor::Green),
+            )
+            .with_cleared(ClearedState::Uncleared)
+            .with_import_id(Some("YNAB:120000:1999-12-17:1".to_string()))]
         );
 
         assert_eq!(result, "asdf");